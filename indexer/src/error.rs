@@ -0,0 +1,17 @@
+use anchor_lang::prelude::Pubkey;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum IndexerError {
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+
+    #[error("Failed to decode event: {0}")]
+    InvalidEvent(String),
+
+    #[error("Event of unknown fee vault {0}")]
+    UnknownFeeVault(Pubkey),
+
+    #[error("Invalid user index {index} of fee vault {fee_vault}")]
+    InvalidUserIndex { fee_vault: Pubkey, index: u8 },
+}
@@ -0,0 +1,42 @@
+//! Event layouts of program v0.1.1, before vesting, share expiry, protocol fee and transfer fee
+//! were added. Events keep their names, so they share the discriminator of the current events
+//! and are told apart by their layout.
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq)]
+pub struct LegacyUserShare {
+    pub address: Pubkey,
+    pub share: u32,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq)]
+pub struct LegacyInitializeFeeVaultParameters {
+    pub padding: [u64; 8],
+    pub users: Vec<LegacyUserShare>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq)]
+pub struct LegacyEvtInitializeFeeVault {
+    pub fee_vault: Pubkey,
+    pub token_mint: Pubkey,
+    pub owner: Pubkey,
+    pub base: Pubkey,
+    pub params: LegacyInitializeFeeVaultParameters,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq)]
+pub struct LegacyEvtFundFee {
+    pub source_program: Pubkey,
+    pub fee_vault: Pubkey,
+    pub funded_amount: u64,
+    pub fee_per_share: u128,
+    pub payload: Vec<u8>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq)]
+pub struct LegacyEvtClaimFee {
+    pub fee_vault: Pubkey,
+    pub user: Pubkey,
+    pub index: u8,
+    pub claimed_fee: u64,
+}
@@ -0,0 +1,13 @@
+//! Indexer of dynamic fee sharing events.
+//!
+//! Events are emitted by `emit_cpi!` as self inner instructions of the program, so they are
+//! parsed from transaction metadata and replayed into an in-memory model of every fee vault.
+pub mod error;
+pub mod legacy;
+pub mod parser;
+pub mod store;
+
+pub use error::IndexerError;
+pub use legacy::*;
+pub use parser::*;
+pub use store::*;
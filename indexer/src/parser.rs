@@ -0,0 +1,192 @@
+use std::str::FromStr;
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use dynamic_fee_sharing::event::{
+    EvtClaimFee, EvtExpireShare, EvtFreezeVault, EvtFundFee, EvtInitializeFeeVault,
+    EvtMigrateFeeVault, EvtPullFromParent, EvtSetFeeVaultMetadata, EvtUnfreezeVault,
+};
+use serde_json::Value;
+
+use crate::error::IndexerError;
+use crate::legacy::{LegacyEvtClaimFee, LegacyEvtFundFee, LegacyEvtInitializeFeeVault};
+
+pub enum FeeVaultEvent {
+    InitializeFeeVault(EvtInitializeFeeVault),
+    FundFee(EvtFundFee),
+    ClaimFee(EvtClaimFee),
+    ExpireShare(EvtExpireShare),
+    SetFeeVaultMetadata(EvtSetFeeVaultMetadata),
+    FreezeVault(EvtFreezeVault),
+    UnfreezeVault(EvtUnfreezeVault),
+    MigrateFeeVault(EvtMigrateFeeVault),
+    PullFromParent(EvtPullFromParent),
+    LegacyInitializeFeeVault(LegacyEvtInitializeFeeVault),
+    LegacyFundFee(LegacyEvtFundFee),
+    LegacyClaimFee(LegacyEvtClaimFee),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InnerInstruction {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub is_failed: bool,
+    pub inner_instructions: Vec<InnerInstruction>,
+}
+
+/// Decode event from data of a self inner instruction. Events of config and DAMM v2 positions
+/// don't change fee vault state, so they are skipped
+pub fn parse_event(data: &[u8]) -> std::result::Result<Option<FeeVaultEvent>, IndexerError> {
+    let Some(event_data) = data.strip_prefix(EVENT_IX_TAG_LE) else {
+        return Ok(None);
+    };
+    if event_data.len() < 8 {
+        return Err(IndexerError::InvalidEvent(
+            "missing discriminator".to_string(),
+        ));
+    }
+    let (discriminator, payload) = event_data.split_at(8);
+
+    let event = if discriminator == EvtInitializeFeeVault::DISCRIMINATOR {
+        deserialize_with_legacy(
+            payload,
+            FeeVaultEvent::InitializeFeeVault,
+            FeeVaultEvent::LegacyInitializeFeeVault,
+        )?
+    } else if discriminator == EvtFundFee::DISCRIMINATOR {
+        deserialize_with_legacy(
+            payload,
+            FeeVaultEvent::FundFee,
+            FeeVaultEvent::LegacyFundFee,
+        )?
+    } else if discriminator == EvtClaimFee::DISCRIMINATOR {
+        deserialize_with_legacy(
+            payload,
+            FeeVaultEvent::ClaimFee,
+            FeeVaultEvent::LegacyClaimFee,
+        )?
+    } else if discriminator == EvtExpireShare::DISCRIMINATOR {
+        FeeVaultEvent::ExpireShare(deserialize(payload)?)
+    } else if discriminator == EvtSetFeeVaultMetadata::DISCRIMINATOR {
+        FeeVaultEvent::SetFeeVaultMetadata(deserialize(payload)?)
+    } else if discriminator == EvtFreezeVault::DISCRIMINATOR {
+        FeeVaultEvent::FreezeVault(deserialize(payload)?)
+    } else if discriminator == EvtUnfreezeVault::DISCRIMINATOR {
+        FeeVaultEvent::UnfreezeVault(deserialize(payload)?)
+    } else if discriminator == EvtMigrateFeeVault::DISCRIMINATOR {
+        FeeVaultEvent::MigrateFeeVault(deserialize(payload)?)
+    } else if discriminator == EvtPullFromParent::DISCRIMINATOR {
+        FeeVaultEvent::PullFromParent(deserialize(payload)?)
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(event))
+}
+
+/// Payload must be consumed entirely, so an event is never decoded with a layout it doesn't have
+fn deserialize<T: AnchorDeserialize>(payload: &[u8]) -> std::result::Result<T, IndexerError> {
+    T::try_from_slice(payload).map_err(|e| IndexerError::InvalidEvent(e.to_string()))
+}
+
+/// Decode event in current layout, or in the layout of v0.1.1 for events emitted before upgrade
+fn deserialize_with_legacy<T: AnchorDeserialize, L: AnchorDeserialize>(
+    payload: &[u8],
+    current: fn(T) -> FeeVaultEvent,
+    legacy: fn(L) -> FeeVaultEvent,
+) -> std::result::Result<FeeVaultEvent, IndexerError> {
+    match deserialize(payload) {
+        Ok(event) => Ok(current(event)),
+        Err(err) => deserialize(payload).map(legacy).map_err(|_| err),
+    }
+}
+
+impl RecordedTransaction {
+    /// Parse a transaction returned by `getTransaction` rpc with `json` encoding
+    pub fn from_rpc_json(value: &Value) -> std::result::Result<Self, IndexerError> {
+        let invalid = |field: &str| IndexerError::InvalidTransaction(format!("missing {field}"));
+
+        let transaction = value
+            .get("transaction")
+            .ok_or_else(|| invalid("transaction"))?;
+        let meta = value.get("meta").ok_or_else(|| invalid("meta"))?;
+
+        let signature = transaction["signatures"][0]
+            .as_str()
+            .ok_or_else(|| invalid("signatures"))?
+            .to_string();
+        let slot = value["slot"].as_u64().ok_or_else(|| invalid("slot"))?;
+        let block_time = value["blockTime"].as_i64();
+        let is_failed = !meta["err"].is_null();
+
+        // account keys of v0 transaction are static keys followed by loaded addresses
+        let mut account_keys = parse_pubkeys(&transaction["message"]["accountKeys"])?;
+        account_keys.extend(parse_pubkeys(&meta["loadedAddresses"]["writable"])?);
+        account_keys.extend(parse_pubkeys(&meta["loadedAddresses"]["readonly"])?);
+
+        let mut inner_instructions = vec![];
+        for inner in meta["innerInstructions"].as_array().into_iter().flatten() {
+            for instruction in inner["instructions"].as_array().into_iter().flatten() {
+                let program_id_index = instruction["programIdIndex"]
+                    .as_u64()
+                    .ok_or_else(|| invalid("programIdIndex"))?;
+                let program_id = *account_keys
+                    .get(program_id_index as usize)
+                    .ok_or_else(|| invalid("program id"))?;
+                let data = bs58::decode(instruction["data"].as_str().unwrap_or_default())
+                    .into_vec()
+                    .map_err(|e| IndexerError::InvalidTransaction(e.to_string()))?;
+                inner_instructions.push(InnerInstruction { program_id, data });
+            }
+        }
+
+        Ok(Self {
+            signature,
+            slot,
+            block_time,
+            is_failed,
+            inner_instructions,
+        })
+    }
+
+    /// Events emitted by the program in this transaction, in order of emission.
+    /// Event instruction is only accepted when signed by the program event authority, so every
+    /// self inner instruction carrying the event tag is trusted
+    pub fn events(&self) -> std::result::Result<Vec<FeeVaultEvent>, IndexerError> {
+        if self.is_failed {
+            return Ok(vec![]);
+        }
+
+        let mut events = vec![];
+        for instruction in self.inner_instructions.iter() {
+            if instruction.program_id.ne(&dynamic_fee_sharing::ID) {
+                continue;
+            }
+            if let Some(event) = parse_event(&instruction.data)? {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+}
+
+fn parse_pubkeys(value: &Value) -> std::result::Result<Vec<Pubkey>, IndexerError> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|key| {
+            key.as_str()
+                .and_then(|key| Pubkey::from_str(key).ok())
+                .ok_or_else(|| IndexerError::InvalidTransaction(format!("invalid pubkey {key}")))
+        })
+        .collect()
+}
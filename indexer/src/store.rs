@@ -0,0 +1,537 @@
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use dynamic_fee_sharing::constants::FEE_VAULT_VERSION;
+use dynamic_fee_sharing::event::{
+    EvtClaimFee, EvtExpireShare, EvtFreezeVault, EvtFundFee, EvtInitializeFeeVault,
+    EvtMigrateFeeVault, EvtPullFromParent, EvtSetFeeVaultMetadata, EvtUnfreezeVault,
+};
+use dynamic_fee_sharing::state::FeeVault;
+use dynamic_fee_sharing::{FeeVaultMetadataParameters, UserShare};
+
+use crate::error::IndexerError;
+use crate::legacy::{LegacyEvtClaimFee, LegacyEvtFundFee, LegacyEvtInitializeFeeVault};
+use crate::parser::{FeeVaultEvent, RecordedTransaction};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserRecord {
+    pub address: Pubkey,
+    pub share: u32,
+    pub end_timestamp: u64,
+    pub is_expired: u8,
+    pub pending_fee: u64,
+    pub fee_claimed: u64,
+    pub fee_per_share_checkpoint: u128,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub source_program: Pubkey,
+    pub funded_amount: u64,
+    pub protocol_fee: u64,
+    pub transfer_fee: u64,
+    pub reserved_transfer_fee: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaimRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub index: u8,
+    pub user: Pubkey,
+    pub claimed_fee: u64,
+    pub gross_amount: u64,
+    pub transfer_fee: u64,
+    pub net_amount: u64,
+}
+
+/// Fee pulled by a child fee vault from its share of a parent fee vault
+#[derive(Debug, Clone, PartialEq)]
+pub struct PullRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub parent_fee_vault: Pubkey,
+    pub index: u8,
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VaultRecord {
+    pub fee_vault: Pubkey,
+    pub owner: Pubkey,
+    pub token_mint: Pubkey,
+    pub base: Pubkey,
+    pub vesting_duration: u64,
+    pub vesting_end_timestamp: u64,
+    pub last_updated_timestamp: u64,
+    pub users: Vec<UserRecord>,
+    pub total_share: u32,
+    pub total_funded_fee: u64,
+    pub fee_per_share: u128,
+    pub locked_fee: u64,
+    pub transfer_fee_reserve: u64,
+    pub is_frozen: u8,
+    pub is_funding_frozen: u8,
+    pub version: u8, // 0 for vault initialized by v0.1.1 until it is migrated
+    pub metadata: Option<FeeVaultMetadataParameters>,
+    pub fundings: Vec<FundingRecord>,
+    pub claims: Vec<ClaimRecord>,
+    pub pulls: Vec<PullRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub field: String,
+    pub indexed: String,
+    pub on_chain: String,
+}
+
+impl VaultRecord {
+    /// Compare indexed state with fee vault account, and return every mismatched field
+    pub fn reconcile(&self, fee_vault: &FeeVault) -> Vec<Mismatch> {
+        let mut mismatches = vec![];
+        let mut check = |field: String, indexed: String, on_chain: String| {
+            if indexed != on_chain {
+                mismatches.push(Mismatch {
+                    field,
+                    indexed,
+                    on_chain,
+                });
+            }
+        };
+
+        check(
+            "owner".to_string(),
+            self.owner.to_string(),
+            fee_vault.owner.to_string(),
+        );
+        check(
+            "token_mint".to_string(),
+            self.token_mint.to_string(),
+            fee_vault.token_mint.to_string(),
+        );
+        check(
+            "vesting_duration".to_string(),
+            self.vesting_duration.to_string(),
+            fee_vault.vesting_duration.to_string(),
+        );
+        check(
+            "vesting_end_timestamp".to_string(),
+            self.vesting_end_timestamp.to_string(),
+            fee_vault.vesting_end_timestamp.to_string(),
+        );
+        check(
+            "last_updated_timestamp".to_string(),
+            self.last_updated_timestamp.to_string(),
+            fee_vault.last_updated_timestamp.to_string(),
+        );
+        check(
+            "total_share".to_string(),
+            self.total_share.to_string(),
+            fee_vault.total_share.to_string(),
+        );
+        check(
+            "total_funded_fee".to_string(),
+            self.total_funded_fee.to_string(),
+            fee_vault.total_funded_fee.to_string(),
+        );
+        check(
+            "fee_per_share".to_string(),
+            self.fee_per_share.to_string(),
+            fee_vault.fee_per_share.to_string(),
+        );
+        check(
+            "locked_fee".to_string(),
+            self.locked_fee.to_string(),
+            fee_vault.locked_fee.to_string(),
+        );
+        check(
+            "transfer_fee_reserve".to_string(),
+            self.transfer_fee_reserve.to_string(),
+            fee_vault.transfer_fee_reserve.to_string(),
+        );
+        check(
+            "is_frozen".to_string(),
+            self.is_frozen.to_string(),
+            fee_vault.is_frozen.to_string(),
+        );
+        check(
+            "is_funding_frozen".to_string(),
+            self.is_funding_frozen.to_string(),
+            fee_vault.is_funding_frozen.to_string(),
+        );
+        check(
+            "version".to_string(),
+            self.version.to_string(),
+            fee_vault.version.to_string(),
+        );
+
+        for (index, on_chain_user) in fee_vault.users.iter().enumerate() {
+            let user = self.users.get(index).cloned().unwrap_or_default();
+            check(
+                format!("users[{index}].address"),
+                user.address.to_string(),
+                on_chain_user.address.to_string(),
+            );
+            check(
+                format!("users[{index}].share"),
+                user.share.to_string(),
+                on_chain_user.share.to_string(),
+            );
+            check(
+                format!("users[{index}].end_timestamp"),
+                user.end_timestamp.to_string(),
+                on_chain_user.end_timestamp.to_string(),
+            );
+            check(
+                format!("users[{index}].is_expired"),
+                user.is_expired.to_string(),
+                on_chain_user.is_expired.to_string(),
+            );
+            check(
+                format!("users[{index}].pending_fee"),
+                user.pending_fee.to_string(),
+                on_chain_user.pending_fee.to_string(),
+            );
+            check(
+                format!("users[{index}].fee_claimed"),
+                user.fee_claimed.to_string(),
+                on_chain_user.fee_claimed.to_string(),
+            );
+            check(
+                format!("users[{index}].fee_per_share_checkpoint"),
+                user.fee_per_share_checkpoint.to_string(),
+                on_chain_user.fee_per_share_checkpoint.to_string(),
+            );
+        }
+
+        mismatches
+    }
+
+    fn get_user_mut(&mut self, index: u8) -> Result<&mut UserRecord, IndexerError> {
+        self.users
+            .get_mut(usize::from(index))
+            .ok_or(IndexerError::InvalidUserIndex {
+                fee_vault: self.fee_vault,
+                index,
+            })
+    }
+}
+
+/// Merge duplicated share holders into the slot of their first occurrence the same way as the
+/// program. Parameters were validated when the event was emitted, so nothing else is checked
+fn merge_duplicated_users(users: &[UserShare]) -> Vec<UserRecord> {
+    let mut merged_users: Vec<UserRecord> = Vec::with_capacity(users.len());
+    for user in users.iter() {
+        match merged_users
+            .iter_mut()
+            .find(|u| u.address.eq(&user.address))
+        {
+            Some(existing_user) => existing_user.share += user.share,
+            None => merged_users.push(UserRecord {
+                address: user.address,
+                share: user.share,
+                end_timestamp: user.end_timestamp,
+                ..Default::default()
+            }),
+        }
+    }
+    merged_users
+}
+
+/// In-memory model of every fee vault, built by replaying events in order of execution
+#[derive(Debug, Default)]
+pub struct VaultStore {
+    vaults: HashMap<Pubkey, VaultRecord>,
+}
+
+impl VaultStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, fee_vault: &Pubkey) -> Option<&VaultRecord> {
+        self.vaults.get(fee_vault)
+    }
+
+    pub fn vaults(&self) -> impl Iterator<Item = &VaultRecord> {
+        self.vaults.values()
+    }
+
+    pub fn apply_transaction(
+        &mut self,
+        transaction: &RecordedTransaction,
+    ) -> Result<(), IndexerError> {
+        for event in transaction.events()? {
+            self.apply_event(&transaction.signature, transaction.slot, event)?;
+        }
+        Ok(())
+    }
+
+    pub fn apply_event(
+        &mut self,
+        signature: &str,
+        slot: u64,
+        event: FeeVaultEvent,
+    ) -> Result<(), IndexerError> {
+        match event {
+            FeeVaultEvent::InitializeFeeVault(event) => self.apply_initialize_fee_vault(event),
+            FeeVaultEvent::FundFee(event) => self.apply_fund_fee(signature, slot, event),
+            FeeVaultEvent::ClaimFee(event) => self.apply_claim_fee(signature, slot, event),
+            FeeVaultEvent::ExpireShare(event) => self.apply_expire_share(event),
+            FeeVaultEvent::SetFeeVaultMetadata(event) => self.apply_set_fee_vault_metadata(event),
+            FeeVaultEvent::FreezeVault(event) => self.apply_freeze_vault(event),
+            FeeVaultEvent::UnfreezeVault(event) => self.apply_unfreeze_vault(event),
+            FeeVaultEvent::MigrateFeeVault(event) => self.apply_migrate_fee_vault(event),
+            FeeVaultEvent::PullFromParent(event) => {
+                self.apply_pull_from_parent(signature, slot, event)
+            }
+            FeeVaultEvent::LegacyInitializeFeeVault(event) => {
+                self.apply_legacy_initialize_fee_vault(event)
+            }
+            FeeVaultEvent::LegacyFundFee(event) => {
+                self.apply_legacy_fund_fee(signature, slot, event)
+            }
+            FeeVaultEvent::LegacyClaimFee(event) => {
+                self.apply_legacy_claim_fee(signature, slot, event)
+            }
+        }
+    }
+
+    fn apply_initialize_fee_vault(
+        &mut self,
+        event: EvtInitializeFeeVault,
+    ) -> Result<(), IndexerError> {
+        let users = merge_duplicated_users(&event.params.users);
+        let total_share = users.iter().map(|user| user.share).sum();
+
+        self.vaults.insert(
+            event.fee_vault,
+            VaultRecord {
+                fee_vault: event.fee_vault,
+                owner: event.owner,
+                token_mint: event.token_mint,
+                base: event.base,
+                vesting_duration: event.params.vesting_duration,
+                users,
+                total_share,
+                version: FEE_VAULT_VERSION,
+                metadata: event.params.metadata,
+                ..Default::default()
+            },
+        );
+        Ok(())
+    }
+
+    fn apply_fund_fee(
+        &mut self,
+        signature: &str,
+        slot: u64,
+        event: EvtFundFee,
+    ) -> Result<(), IndexerError> {
+        let vault = self.get_vault_mut(&event.fee_vault)?;
+        vault.total_funded_fee = event.total_funded_fee;
+        vault.fee_per_share = event.fee_per_share;
+        vault.locked_fee = event.locked_fee;
+        vault.transfer_fee_reserve = event.transfer_fee_reserve;
+        vault.total_share = event.total_share;
+        vault.vesting_end_timestamp = event.vesting_end_timestamp;
+        vault.last_updated_timestamp = event.last_updated_timestamp;
+        vault.fundings.push(FundingRecord {
+            signature: signature.to_string(),
+            slot,
+            source_program: event.source_program,
+            funded_amount: event.funded_amount,
+            protocol_fee: event.protocol_fee,
+            transfer_fee: event.transfer_fee,
+            reserved_transfer_fee: event.reserved_transfer_fee,
+        });
+        Ok(())
+    }
+
+    fn apply_claim_fee(
+        &mut self,
+        signature: &str,
+        slot: u64,
+        event: EvtClaimFee,
+    ) -> Result<(), IndexerError> {
+        let vault = self.get_vault_mut(&event.fee_vault)?;
+        vault.fee_per_share = event.fee_per_share;
+        vault.locked_fee = event.locked_fee;
+        vault.transfer_fee_reserve = event.transfer_fee_reserve;
+        vault.total_share = event.total_share;
+        vault.vesting_end_timestamp = event.vesting_end_timestamp;
+        vault.last_updated_timestamp = event.last_updated_timestamp;
+
+        let user = vault.get_user_mut(event.index)?;
+        user.fee_claimed = event.fee_claimed;
+        user.fee_per_share_checkpoint = event.fee_per_share_checkpoint;
+        user.pending_fee = event.pending_fee;
+        user.is_expired = event.is_expired;
+
+        vault.claims.push(ClaimRecord {
+            signature: signature.to_string(),
+            slot,
+            index: event.index,
+            user: event.user,
+            claimed_fee: event.claimed_fee,
+            gross_amount: event.gross_amount,
+            transfer_fee: event.transfer_fee,
+            net_amount: event.net_amount,
+        });
+        Ok(())
+    }
+
+    fn apply_expire_share(&mut self, event: EvtExpireShare) -> Result<(), IndexerError> {
+        let vault = self.get_vault_mut(&event.fee_vault)?;
+        vault.total_share = event.total_share;
+
+        let user = vault.get_user_mut(event.index)?;
+        user.is_expired = 1;
+        user.pending_fee = event.pending_fee;
+        user.fee_per_share_checkpoint = event.fee_per_share_checkpoint;
+        Ok(())
+    }
+
+    fn apply_set_fee_vault_metadata(
+        &mut self,
+        event: EvtSetFeeVaultMetadata,
+    ) -> Result<(), IndexerError> {
+        let vault = self.get_vault_mut(&event.fee_vault)?;
+        vault.metadata = Some(event.metadata);
+        Ok(())
+    }
+
+    fn apply_freeze_vault(&mut self, event: EvtFreezeVault) -> Result<(), IndexerError> {
+        let vault = self.get_vault_mut(&event.fee_vault)?;
+        vault.is_frozen = 1;
+        vault.is_funding_frozen = event.freeze_funding.into();
+        Ok(())
+    }
+
+    fn apply_unfreeze_vault(&mut self, event: EvtUnfreezeVault) -> Result<(), IndexerError> {
+        let vault = self.get_vault_mut(&event.fee_vault)?;
+        vault.is_frozen = 0;
+        vault.is_funding_frozen = 0;
+        Ok(())
+    }
+
+    fn apply_migrate_fee_vault(&mut self, event: EvtMigrateFeeVault) -> Result<(), IndexerError> {
+        let vault = self.get_vault_mut(&event.fee_vault)?;
+        vault.version = event.version;
+        Ok(())
+    }
+
+    /// Claim from parent and funding of child are applied by their own events, emitted before
+    fn apply_pull_from_parent(
+        &mut self,
+        signature: &str,
+        slot: u64,
+        event: EvtPullFromParent,
+    ) -> Result<(), IndexerError> {
+        let vault = self.get_vault_mut(&event.child_fee_vault)?;
+        vault.pulls.push(PullRecord {
+            signature: signature.to_string(),
+            slot,
+            parent_fee_vault: event.parent_fee_vault,
+            index: event.index,
+            amount: event.amount,
+        });
+        Ok(())
+    }
+
+    /// Vault of v0.1.1 keeps share holders in the order they are passed, without vesting
+    fn apply_legacy_initialize_fee_vault(
+        &mut self,
+        event: LegacyEvtInitializeFeeVault,
+    ) -> Result<(), IndexerError> {
+        let users: Vec<UserRecord> = event
+            .params
+            .users
+            .iter()
+            .map(|user| UserRecord {
+                address: user.address,
+                share: user.share,
+                ..Default::default()
+            })
+            .collect();
+        let total_share = users.iter().map(|user| user.share).sum();
+
+        self.vaults.insert(
+            event.fee_vault,
+            VaultRecord {
+                fee_vault: event.fee_vault,
+                owner: event.owner,
+                token_mint: event.token_mint,
+                base: event.base,
+                users,
+                total_share,
+                ..Default::default()
+            },
+        );
+        Ok(())
+    }
+
+    /// Event of v0.1.1 only carries fee_per_share as post-state, funded fee is fully released
+    fn apply_legacy_fund_fee(
+        &mut self,
+        signature: &str,
+        slot: u64,
+        event: LegacyEvtFundFee,
+    ) -> Result<(), IndexerError> {
+        let vault = self.get_vault_mut(&event.fee_vault)?;
+        vault.total_funded_fee = vault
+            .total_funded_fee
+            .checked_add(event.funded_amount)
+            .ok_or_else(|| IndexerError::InvalidEvent("total funded fee overflow".to_string()))?;
+        vault.fee_per_share = event.fee_per_share;
+        vault.fundings.push(FundingRecord {
+            signature: signature.to_string(),
+            slot,
+            source_program: event.source_program,
+            funded_amount: event.funded_amount,
+            protocol_fee: 0,
+            transfer_fee: 0,
+            reserved_transfer_fee: 0,
+        });
+        Ok(())
+    }
+
+    /// Claim of v0.1.1 moves user checkpoint to fee_per_share of the vault and pays all fee out
+    fn apply_legacy_claim_fee(
+        &mut self,
+        signature: &str,
+        slot: u64,
+        event: LegacyEvtClaimFee,
+    ) -> Result<(), IndexerError> {
+        let vault = self.get_vault_mut(&event.fee_vault)?;
+        let fee_per_share = vault.fee_per_share;
+
+        let user = vault.get_user_mut(event.index)?;
+        user.fee_claimed = user
+            .fee_claimed
+            .checked_add(event.claimed_fee)
+            .ok_or_else(|| IndexerError::InvalidEvent("fee claimed overflow".to_string()))?;
+        user.fee_per_share_checkpoint = fee_per_share;
+
+        vault.claims.push(ClaimRecord {
+            signature: signature.to_string(),
+            slot,
+            index: event.index,
+            user: event.user,
+            claimed_fee: event.claimed_fee,
+            gross_amount: event.claimed_fee,
+            transfer_fee: 0,
+            net_amount: event.claimed_fee,
+        });
+        Ok(())
+    }
+
+    fn get_vault_mut(&mut self, fee_vault: &Pubkey) -> Result<&mut VaultRecord, IndexerError> {
+        self.vaults
+            .get_mut(fee_vault)
+            .ok_or(IndexerError::UnknownFeeVault(*fee_vault))
+    }
+}
@@ -0,0 +1,584 @@
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::*;
+use anchor_lang::{Discriminator, Event};
+use dynamic_fee_sharing::constants::{FEE_VAULT_VERSION, MAX_USER};
+use dynamic_fee_sharing::event::{
+    EvtClaimFee, EvtExpireShare, EvtFreezeVault, EvtFundFee, EvtInitializeFeeVault,
+    EvtMigrateFeeVault, EvtPullFromParent, EvtSetFeeVaultMetadata, EvtUnfreezeVault,
+};
+use dynamic_fee_sharing::state::{FeeVault, FeeVaultKeys};
+use dynamic_fee_sharing::{
+    DuplicateUserMode, FeeVaultMetadataParameters, InitializeFeeVaultParameters, UserShare,
+};
+use dynamic_fee_sharing_indexer::{
+    IndexerError, LegacyEvtClaimFee, LegacyEvtFundFee, LegacyEvtInitializeFeeVault,
+    LegacyInitializeFeeVaultParameters, LegacyUserShare, PullRecord, RecordedTransaction,
+    VaultStore,
+};
+use serde_json::{json, Value};
+
+/// Build a transaction in the shape of `getTransaction` rpc output with `json` encoding, with
+/// events emitted as self inner instructions signed by event authority the same way as
+/// `emit_cpi!`. Events are not captured from a cluster, they are produced by driving `FeeVault`
+/// through the same state transitions as the program
+fn build_transaction(signature: &str, slot: u64, events: &[Vec<u8>], is_failed: bool) -> Value {
+    let other_program = Pubkey::new_unique();
+    let event_authority =
+        Pubkey::find_program_address(&[b"__event_authority"], &dynamic_fee_sharing::ID).0;
+    let account_keys = vec![
+        Pubkey::new_unique().to_string(),
+        event_authority.to_string(),
+        dynamic_fee_sharing::ID.to_string(),
+    ];
+    let mut instructions = vec![];
+    for event in events {
+        let data = [EVENT_IX_TAG_LE, event].concat();
+        instructions.push(json!({
+            "programIdIndex": 2,
+            "accounts": [1],
+            "data": bs58::encode(&data).into_string(),
+            "stackHeight": 2,
+        }));
+        // same data from another program must be ignored
+        instructions.push(json!({
+            "programIdIndex": 3,
+            "accounts": [1],
+            "data": bs58::encode(&data).into_string(),
+            "stackHeight": 2,
+        }));
+    }
+
+    json!({
+        "slot": slot,
+        "blockTime": null,
+        "version": 0,
+        "transaction": {
+            "signatures": [signature],
+            "message": {
+                "accountKeys": account_keys,
+                "header": {
+                    "numRequiredSignatures": 1,
+                    "numReadonlySignedAccounts": 0,
+                    "numReadonlyUnsignedAccounts": 2,
+                },
+                "instructions": [{ "programIdIndex": 2, "accounts": [0], "data": "", "stackHeight": null }],
+                "recentBlockhash": Pubkey::new_unique().to_string(),
+                "addressTableLookups": [],
+            },
+        },
+        "meta": {
+            "err": if is_failed { json!({ "InstructionError": [0, { "Custom": 6000 }] }) } else { Value::Null },
+            "fee": 5000,
+            "innerInstructions": [{ "index": 0, "instructions": instructions }],
+            "loadedAddresses": { "writable": [other_program.to_string()], "readonly": [] },
+            "logMessages": [],
+        },
+    })
+}
+
+fn fund_event(fee_vault_key: Pubkey, fee_vault: &FeeVault, funded_amount: u64) -> Vec<u8> {
+    EvtFundFee {
+        source_program: Pubkey::default(),
+        fee_vault: fee_vault_key,
+        token_mint: fee_vault.token_mint,
+        funded_amount,
+        protocol_fee: 0,
+        transfer_fee: 0,
+        reserved_transfer_fee: 0,
+        total_funded_fee: fee_vault.total_funded_fee,
+        fee_per_share: fee_vault.fee_per_share,
+        locked_fee: fee_vault.locked_fee,
+        transfer_fee_reserve: fee_vault.transfer_fee_reserve,
+        total_share: fee_vault.total_share,
+        vesting_end_timestamp: fee_vault.vesting_end_timestamp,
+        last_updated_timestamp: fee_vault.last_updated_timestamp,
+        payload: vec![],
+    }
+    .data()
+}
+
+fn expire_events(
+    fee_vault_key: Pubkey,
+    fee_vault: &FeeVault,
+    expired_flags: [u8; MAX_USER],
+) -> Vec<Vec<u8>> {
+    fee_vault
+        .users
+        .iter()
+        .zip(expired_flags)
+        .enumerate()
+        .filter(|(_, (user, was_expired))| user.is_expired == 1 && *was_expired == 0)
+        .map(|(index, (user, _))| {
+            EvtExpireShare {
+                fee_vault: fee_vault_key,
+                user: user.address,
+                index: index as u8,
+                pending_fee: user.pending_fee,
+                fee_per_share_checkpoint: user.fee_per_share_checkpoint,
+                total_share: fee_vault.total_share,
+            }
+            .data()
+        })
+        .collect()
+}
+
+fn claim_event(
+    fee_vault_key: Pubkey,
+    fee_vault: &FeeVault,
+    index: u8,
+    claimed_fee: u64,
+) -> Vec<u8> {
+    let user = &fee_vault.users[usize::from(index)];
+    EvtClaimFee {
+        fee_vault: fee_vault_key,
+        token_mint: fee_vault.token_mint,
+        user: user.address,
+        index,
+        claimed_fee,
+        gross_amount: claimed_fee,
+        transfer_fee: 0,
+        net_amount: claimed_fee,
+        fee_claimed: user.fee_claimed,
+        fee_per_share_checkpoint: user.fee_per_share_checkpoint,
+        pending_fee: user.pending_fee,
+        is_expired: user.is_expired,
+        fee_per_share: fee_vault.fee_per_share,
+        locked_fee: fee_vault.locked_fee,
+        transfer_fee_reserve: fee_vault.transfer_fee_reserve,
+        total_share: fee_vault.total_share,
+        vesting_end_timestamp: fee_vault.vesting_end_timestamp,
+        last_updated_timestamp: fee_vault.last_updated_timestamp,
+    }
+    .data()
+}
+
+/// Drive fee vault through a vesting and share expiry scenario, and build the transactions
+/// carrying the events the program emits
+fn build_history() -> (Pubkey, FeeVault, Vec<Value>) {
+    let fee_vault_key = Pubkey::new_unique();
+    let user_a = Pubkey::new_unique();
+    let user_b = Pubkey::new_unique();
+    let user_c = Pubkey::new_unique();
+    let params = InitializeFeeVaultParameters {
+        vesting_duration: 100,
+        duplicate_user_mode: DuplicateUserMode::Merge.into(),
+        transfer_fee_mode: 0,
+        allow_permanent_delegate: 0,
+        padding_0: [0; 5],
+        padding: [0; 6],
+        users: vec![
+            UserShare {
+                address: user_a,
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: user_b,
+                share: 50,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: user_a,
+                share: 50,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: user_c,
+                share: 50,
+                end_timestamp: 150,
+            },
+        ],
+        metadata: Some(FeeVaultMetadataParameters {
+            name: "Partner vault".to_string(),
+            uri: String::new(),
+            labels: vec!["Partner A".to_string()],
+        }),
+    };
+
+    let mut fee_vault = FeeVault::default();
+    let owner = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    fee_vault
+        .initialize(
+            &FeeVaultKeys {
+                owner,
+                token_mint,
+                token_vault: Pubkey::new_unique(),
+                ..Default::default()
+            },
+            params.vesting_duration,
+            params.transfer_fee_mode,
+            &params.validate(0).unwrap(),
+        )
+        .unwrap();
+
+    let mut transactions = vec![];
+    let initialize_event = EvtInitializeFeeVault {
+        fee_vault: fee_vault_key,
+        token_mint,
+        owner,
+        base: Pubkey::default(),
+        params,
+    }
+    .data();
+    transactions.push(build_transaction("init", 1, &[initialize_event], false));
+
+    fee_vault.fund_fee(1_000, 0).unwrap();
+    let event = fund_event(fee_vault_key, &fee_vault, 1_000);
+    transactions.push(build_transaction("fund_0", 2, &[event], false));
+
+    // failed transaction is never applied
+    let event = fund_event(fee_vault_key, &fee_vault, 1_000_000);
+    transactions.push(build_transaction("failed", 3, &[event], true));
+
+    let claimed_fee = fee_vault.validate_and_claim_fee(0, &user_a, 50).unwrap();
+    let event = claim_event(fee_vault_key, &fee_vault, 0, claimed_fee);
+    transactions.push(build_transaction("claim_a", 4, &[event], false));
+
+    // share of user c expires on the way to funding
+    let expired_flags = fee_vault.expired_flags();
+    fee_vault.fund_fee(600, 200).unwrap();
+    let mut events = expire_events(fee_vault_key, &fee_vault, expired_flags);
+    events.push(fund_event(fee_vault_key, &fee_vault, 600));
+    let claimed_fee = fee_vault.validate_and_claim_fee(1, &user_b, 250).unwrap();
+    events.push(claim_event(fee_vault_key, &fee_vault, 1, claimed_fee));
+    transactions.push(build_transaction("fund_and_claim_b", 5, &events, false));
+
+    (fee_vault_key, fee_vault, transactions)
+}
+
+fn replay(transactions: &[Value]) -> std::result::Result<VaultStore, IndexerError> {
+    let mut store = VaultStore::new();
+    for transaction in transactions {
+        store.apply_transaction(&RecordedTransaction::from_rpc_json(transaction)?)?;
+    }
+    Ok(store)
+}
+
+#[test]
+fn test_replay_rebuilds_fee_vault_state() {
+    let (fee_vault_key, fee_vault, transactions) = build_history();
+    let store = replay(&transactions).unwrap();
+
+    let vault = store.get(&fee_vault_key).unwrap();
+    assert_eq!(vault.reconcile(&fee_vault), vec![]);
+
+    // duplicated user is merged into one slot
+    assert_eq!(vault.users.len(), 3);
+    assert_eq!(vault.users[0].share, 150);
+    assert_eq!(vault.vesting_duration, 100);
+
+    // expired share keeps fee accrued before expiry
+    assert_eq!(vault.users[2].is_expired, 1);
+    assert!(vault.users[2].pending_fee > 0);
+    assert_eq!(vault.total_share, 200);
+
+    let fundings: Vec<(&str, u64)> = vault
+        .fundings
+        .iter()
+        .map(|funding| (funding.signature.as_str(), funding.funded_amount))
+        .collect();
+    assert_eq!(fundings, vec![("fund_0", 1_000), ("fund_and_claim_b", 600)]);
+
+    let claims: Vec<(u8, u64)> = vault
+        .claims
+        .iter()
+        .map(|claim| (claim.index, claim.claimed_fee))
+        .collect();
+    assert_eq!(
+        claims,
+        vec![
+            (0, fee_vault.users[0].fee_claimed),
+            (1, fee_vault.users[1].fee_claimed)
+        ]
+    );
+}
+
+#[test]
+fn test_replay_tracks_latest_metadata() {
+    let (fee_vault_key, _, mut transactions) = build_history();
+    let store = replay(&transactions).unwrap();
+    let vault = store.get(&fee_vault_key).unwrap();
+    assert_eq!(vault.metadata.as_ref().unwrap().name, "Partner vault");
+
+    let metadata = FeeVaultMetadataParameters {
+        name: "Partner vault".to_string(),
+        uri: "https://example.com/vault.json".to_string(),
+        labels: vec!["Partner A - marketing".to_string(), "Partner B".to_string()],
+    };
+    let event = EvtSetFeeVaultMetadata {
+        fee_vault: fee_vault_key,
+        metadata: metadata.clone(),
+    }
+    .data();
+    transactions.push(build_transaction("set_metadata", 6, &[event], false));
+
+    let store = replay(&transactions).unwrap();
+    assert_eq!(store.get(&fee_vault_key).unwrap().metadata, Some(metadata));
+}
+
+#[test]
+fn test_reconcile_detects_missing_event() {
+    let (fee_vault_key, fee_vault, mut transactions) = build_history();
+    // drop claim of user a
+    transactions.remove(3);
+    let store = replay(&transactions).unwrap();
+
+    let mismatches = store.get(&fee_vault_key).unwrap().reconcile(&fee_vault);
+    let fields: Vec<&str> = mismatches
+        .iter()
+        .map(|mismatch| mismatch.field.as_str())
+        .collect();
+    assert_eq!(
+        fields,
+        vec!["users[0].fee_claimed", "users[0].fee_per_share_checkpoint"]
+    );
+}
+
+#[test]
+fn test_reconcile_detects_missing_expire_share_event() {
+    let (fee_vault_key, fee_vault, mut transactions) = build_history();
+    // drop the expire share event in front of funding
+    let inner_instructions = transactions[4]["meta"]["innerInstructions"][0]["instructions"]
+        .as_array_mut()
+        .unwrap();
+    inner_instructions.drain(..2);
+    let store = replay(&transactions).unwrap();
+
+    let mismatches = store.get(&fee_vault_key).unwrap().reconcile(&fee_vault);
+    let fields: Vec<&str> = mismatches
+        .iter()
+        .map(|mismatch| mismatch.field.as_str())
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            "users[2].is_expired",
+            "users[2].pending_fee",
+            "users[2].fee_per_share_checkpoint"
+        ]
+    );
+}
+
+#[test]
+fn test_replay_rejects_event_of_unknown_fee_vault() {
+    let (fee_vault_key, _, transactions) = build_history();
+    let result = replay(&transactions[1..]);
+    assert!(matches!(
+        result,
+        Err(IndexerError::UnknownFeeVault(fee_vault)) if fee_vault == fee_vault_key
+    ));
+}
+
+/// Event emitted by v0.1.1, which has the discriminator of the current event with the same name
+fn legacy_event<T: AnchorSerialize>(discriminator: &[u8], event: &T) -> Vec<u8> {
+    [discriminator, &event.try_to_vec().unwrap()].concat()
+}
+
+fn mismatched_fields(
+    store: &VaultStore,
+    fee_vault_key: &Pubkey,
+    fee_vault: &FeeVault,
+) -> Vec<String> {
+    store
+        .get(fee_vault_key)
+        .unwrap()
+        .reconcile(fee_vault)
+        .into_iter()
+        .map(|mismatch| mismatch.field)
+        .collect()
+}
+
+#[test]
+fn test_replay_decodes_legacy_events() {
+    let fee_vault_key = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    let user_a = Pubkey::new_unique();
+    let user_b = Pubkey::new_unique();
+
+    // v0.1.1 has no vesting, so fee vault of current program without vesting goes through the
+    // same state transitions
+    let mut fee_vault = FeeVault::default();
+    fee_vault
+        .initialize(
+            &FeeVaultKeys {
+                owner,
+                token_mint,
+                token_vault: Pubkey::new_unique(),
+                ..Default::default()
+            },
+            0,
+            0,
+            &[
+                UserShare {
+                    address: user_a,
+                    share: 100,
+                    end_timestamp: 0,
+                },
+                UserShare {
+                    address: user_b,
+                    share: 300,
+                    end_timestamp: 0,
+                },
+            ],
+        )
+        .unwrap();
+
+    let mut transactions = vec![];
+    let event = legacy_event(
+        EvtInitializeFeeVault::DISCRIMINATOR,
+        &LegacyEvtInitializeFeeVault {
+            fee_vault: fee_vault_key,
+            token_mint,
+            owner,
+            base: Pubkey::default(),
+            params: LegacyInitializeFeeVaultParameters {
+                padding: [0; 8],
+                users: vec![
+                    LegacyUserShare {
+                        address: user_a,
+                        share: 100,
+                    },
+                    LegacyUserShare {
+                        address: user_b,
+                        share: 300,
+                    },
+                ],
+            },
+        },
+    );
+    transactions.push(build_transaction("legacy_init", 1, &[event], false));
+
+    fee_vault.fund_fee(1_000, 0).unwrap();
+    let event = legacy_event(
+        EvtFundFee::DISCRIMINATOR,
+        &LegacyEvtFundFee {
+            source_program: Pubkey::default(),
+            fee_vault: fee_vault_key,
+            funded_amount: 1_000,
+            fee_per_share: fee_vault.fee_per_share,
+            payload: vec![],
+        },
+    );
+    transactions.push(build_transaction("legacy_fund", 2, &[event], false));
+
+    let claimed_fee = fee_vault.validate_and_claim_fee(1, &user_b, 0).unwrap();
+    let event = legacy_event(
+        EvtClaimFee::DISCRIMINATOR,
+        &LegacyEvtClaimFee {
+            fee_vault: fee_vault_key,
+            user: user_b,
+            index: 1,
+            claimed_fee,
+        },
+    );
+    transactions.push(build_transaction("legacy_claim", 3, &[event], false));
+
+    // only version differs until the vault is migrated
+    let store = replay(&transactions).unwrap();
+    assert_eq!(
+        mismatched_fields(&store, &fee_vault_key, &fee_vault),
+        vec!["version"]
+    );
+    let vault = store.get(&fee_vault_key).unwrap();
+    assert_eq!(vault.fundings[0].funded_amount, 1_000);
+    assert_eq!(vault.claims[0].claimed_fee, claimed_fee);
+    assert_eq!(vault.users[1].fee_claimed, 750);
+
+    let event = EvtMigrateFeeVault {
+        fee_vault: fee_vault_key,
+        version: FEE_VAULT_VERSION,
+    }
+    .data();
+    transactions.push(build_transaction("migrate", 4, &[event], false));
+
+    // events of current layout are applied to migrated vault
+    fee_vault.fund_fee(400, 10).unwrap();
+    let event = fund_event(fee_vault_key, &fee_vault, 400);
+    transactions.push(build_transaction("fund", 5, &[event], false));
+
+    let store = replay(&transactions).unwrap();
+    assert_eq!(
+        mismatched_fields(&store, &fee_vault_key, &fee_vault),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn test_replay_rejects_event_of_unknown_layout() {
+    let (_, _, mut transactions) = build_history();
+    let event = [EvtClaimFee::DISCRIMINATOR, &[0u8; 10]].concat();
+    transactions.push(build_transaction("malformed", 6, &[event], false));
+
+    assert!(matches!(
+        replay(&transactions),
+        Err(IndexerError::InvalidEvent(_))
+    ));
+}
+
+#[test]
+fn test_replay_tracks_freeze() {
+    let (fee_vault_key, mut fee_vault, mut transactions) = build_history();
+
+    fee_vault.freeze(true);
+    let event = EvtFreezeVault {
+        fee_vault: fee_vault_key,
+        freeze_funding: true,
+    }
+    .data();
+    transactions.push(build_transaction("freeze", 6, &[event], false));
+
+    let store = replay(&transactions).unwrap();
+    assert_eq!(
+        mismatched_fields(&store, &fee_vault_key, &fee_vault),
+        Vec::<String>::new()
+    );
+    let vault = store.get(&fee_vault_key).unwrap();
+    assert_eq!((vault.is_frozen, vault.is_funding_frozen), (1, 1));
+
+    fee_vault.unfreeze();
+    let event = EvtUnfreezeVault {
+        fee_vault: fee_vault_key,
+    }
+    .data();
+    transactions.push(build_transaction("unfreeze", 7, &[event], false));
+
+    let store = replay(&transactions).unwrap();
+    assert_eq!(
+        mismatched_fields(&store, &fee_vault_key, &fee_vault),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn test_replay_records_pull_from_parent() {
+    let (fee_vault_key, fee_vault, mut transactions) = build_history();
+    let parent_fee_vault = Pubkey::new_unique();
+
+    // claim from parent and funding of child are emitted as their own events before it
+    let event = EvtPullFromParent {
+        parent_fee_vault,
+        child_fee_vault: fee_vault_key,
+        index: 2,
+        amount: 300,
+    }
+    .data();
+    transactions.push(build_transaction("pull", 6, &[event], false));
+
+    let store = replay(&transactions).unwrap();
+    assert_eq!(
+        mismatched_fields(&store, &fee_vault_key, &fee_vault),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        store.get(&fee_vault_key).unwrap().pulls,
+        vec![PullRecord {
+            signature: "pull".to_string(),
+            slot: 6,
+            parent_fee_vault,
+            index: 2,
+            amount: 300,
+        }]
+    );
+}
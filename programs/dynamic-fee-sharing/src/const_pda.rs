@@ -13,7 +13,33 @@ pub mod fee_vault_authority {
     pub const BUMP: u8 = FEE_VAULT_AUTHORITY_AND_BUMP.1;
 }
 
-// Potential optimization on event authority too since anchor internally do Pubkey::find_program_address during runtime.
+pub mod config {
+    use super::*;
+
+    const CONFIG_AND_BUMP: ([u8; 32], u8) = ed25519::derive_program_address(
+        &[crate::constants::seeds::CONFIG_PREFIX],
+        &crate::ID_CONST.to_bytes(),
+    );
+
+    pub const ID: Pubkey = Pubkey::new_from_array(CONFIG_AND_BUMP.0);
+    pub const BUMP: u8 = CONFIG_AND_BUMP.1;
+}
+
+// anchor derives event authority bump with Pubkey::find_program_address while validating accounts of
+// #[event_cpi], hot paths declare event authority with this address and emit through emit_event_cpi.
+// Only that outer derivation is saved: the __event_dispatch generated by anchor still calls
+// Pubkey::find_program_address inside every event self cpi
+pub mod event_authority {
+    use super::*;
+
+    const EVENT_AUTHORITY_AND_BUMP: ([u8; 32], u8) = ed25519::derive_program_address(
+        &[crate::constants::seeds::EVENT_AUTHORITY_PREFIX],
+        &crate::ID_CONST.to_bytes(),
+    );
+
+    pub const ID: Pubkey = Pubkey::new_from_array(EVENT_AUTHORITY_AND_BUMP.0);
+    pub const BUMP: u8 = EVENT_AUTHORITY_AND_BUMP.1;
+}
 
 #[cfg(test)]
 mod tests {
@@ -28,4 +54,22 @@ mod tests {
         assert_eq!(fee_vault_authority::ID, derived_pool_authority);
         assert_eq!(fee_vault_authority::BUMP, derived_bump);
     }
+
+    #[test]
+    fn test_const_config() {
+        let (derived_config, derived_bump) =
+            Pubkey::find_program_address(&[crate::constants::seeds::CONFIG_PREFIX], &crate::ID);
+        assert_eq!(config::ID, derived_config);
+        assert_eq!(config::BUMP, derived_bump);
+    }
+
+    #[test]
+    fn test_const_event_authority() {
+        let (derived_event_authority, derived_bump) = Pubkey::find_program_address(
+            &[crate::constants::seeds::EVENT_AUTHORITY_PREFIX],
+            &crate::ID,
+        );
+        assert_eq!(event_authority::ID, derived_event_authority);
+        assert_eq!(event_authority::BUMP, derived_bump);
+    }
 }
@@ -3,51 +3,133 @@ use anchor_lang::Discriminator;
 
 pub const MAX_USER: usize = 5;
 pub const PRECISION_SCALE: u8 = 64;
+pub const DISCRIMINATOR_LEN: usize = 8;
+pub const MAX_BASIS_POINT: u16 = 10_000;
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 2_000;
+pub const MAX_TRANSFER_HOOK_PROGRAM: usize = 4;
+pub const FEE_VAULT_VERSION: u8 = 1;
+pub const MAX_METADATA_NAME_LEN: usize = 32;
+pub const MAX_METADATA_URI_LEN: usize = 128;
+pub const MAX_METADATA_LABEL_LEN: usize = 32;
+pub const MAX_VESTING_DURATION: u64 = 10 * 365 * 24 * 60 * 60; // 10 years
 
 pub mod seeds {
     pub const FEE_VAULT_PREFIX: &[u8] = b"fee_vault";
     pub const FEE_VAULT_AUTHORITY_PREFIX: &[u8] = b"fee_vault_authority";
     pub const TOKEN_VAULT_PREFIX: &[u8] = b"token_vault";
+    pub const CONFIG_PREFIX: &[u8] = b"config";
+    pub const TEMP_WSOL_PREFIX: &[u8] = b"temp_wsol";
+    pub const FEE_VAULT_METADATA_PREFIX: &[u8] = b"fee_vault_metadata";
+    pub const EVENT_AUTHORITY_PREFIX: &[u8] = b"__event_authority"; // same seed as anchor event_cpi
 }
 
-// (program_id, instruction, index_of_token_vault_account)
-// TODO should find a way to avoid hardcoding index of token_vault_account
-pub static WHITELISTED_ACTIONS: [(Pubkey, &[u8], usize); 7] = [
+pub enum ClaimedTokenReceiver {
+    TokenVault,
+    // source program only pays to associated token account of the receiver, claimed token is
+    // swept from fee vault's ATA into token vault after cpi
+    FeeVaultAta {
+        token_mint_index: usize,
+        token_program_index: usize,
+    },
+}
+
+pub struct WhitelistedAction {
+    pub program_id: Pubkey,
+    pub discriminator: &'static [u8],
+    pub payload_len: usize, // discriminator and serialized instruction args
+    pub number_of_accounts: usize,
+    pub receiver_index: usize, // token account receiving the claimed token
+    pub receiver: ClaimedTokenReceiver,
+    pub fee_vault_index: usize, // the only slot fee vault is allowed to sign for
+}
+
+// TODO should find a way to avoid hardcoding index of accounts
+// DBC protocol_withdraw_surplus is not supported since it only pays to the protocol treasury
+pub static WHITELISTED_ACTIONS: [WhitelistedAction; 9] = [
     // damm v2
-    (
-        damm_v2::ID,
-        damm_v2::client::args::ClaimPositionFee::DISCRIMINATOR,
-        4,
-    ),
-    (
-        damm_v2::ID,
-        damm_v2::client::args::ClaimReward::DISCRIMINATOR,
-        5,
-    ),
+    WhitelistedAction {
+        program_id: damm_v2::ID,
+        discriminator: damm_v2::client::args::ClaimPositionFee::DISCRIMINATOR,
+        payload_len: 8,
+        number_of_accounts: 15,
+        receiver_index: 4,
+        receiver: ClaimedTokenReceiver::TokenVault,
+        fee_vault_index: 10,
+    },
+    WhitelistedAction {
+        program_id: damm_v2::ID,
+        discriminator: damm_v2::client::args::ClaimReward::DISCRIMINATOR,
+        payload_len: 10, // reward_index: u8, skip_reward: u8
+        number_of_accounts: 11,
+        receiver_index: 5,
+        receiver: ClaimedTokenReceiver::TokenVault,
+        fee_vault_index: 7,
+    },
+    WhitelistedAction {
+        program_id: damm_v2::ID,
+        discriminator: damm_v2::client::args::ClaimPartnerFee::DISCRIMINATOR,
+        payload_len: 24, // max_amount_a: u64, max_amount_b: u64
+        number_of_accounts: 13,
+        receiver_index: 3,
+        receiver: ClaimedTokenReceiver::TokenVault,
+        fee_vault_index: 8,
+    },
     // DBC
-    (
-        dynamic_bonding_curve::ID,
-        dynamic_bonding_curve::client::args::CreatorWithdrawSurplus::DISCRIMINATOR,
-        3,
-    ),
-    (
-        dynamic_bonding_curve::ID,
-        dynamic_bonding_curve::client::args::ClaimCreatorTradingFee::DISCRIMINATOR,
-        3,
-    ),
-    (
-        dynamic_bonding_curve::ID,
-        dynamic_bonding_curve::client::args::PartnerWithdrawSurplus::DISCRIMINATOR,
-        3,
-    ),
-    (
-        dynamic_bonding_curve::ID,
-        dynamic_bonding_curve::client::args::ClaimTradingFee::DISCRIMINATOR,
-        4,
-    ),
-    (
-        dynamic_bonding_curve::ID,
-        dynamic_bonding_curve::client::args::WithdrawMigrationFee::DISCRIMINATOR,
-        3,
-    ),
+    WhitelistedAction {
+        program_id: dynamic_bonding_curve::ID,
+        discriminator: dynamic_bonding_curve::client::args::CreatorWithdrawSurplus::DISCRIMINATOR,
+        payload_len: 8,
+        number_of_accounts: 10,
+        receiver_index: 3,
+        receiver: ClaimedTokenReceiver::TokenVault,
+        fee_vault_index: 6,
+    },
+    WhitelistedAction {
+        program_id: dynamic_bonding_curve::ID,
+        discriminator: dynamic_bonding_curve::client::args::ClaimCreatorTradingFee::DISCRIMINATOR,
+        payload_len: 24, // max_base_amount: u64, max_quote_amount: u64
+        number_of_accounts: 13,
+        receiver_index: 3,
+        receiver: ClaimedTokenReceiver::TokenVault,
+        fee_vault_index: 8,
+    },
+    WhitelistedAction {
+        program_id: dynamic_bonding_curve::ID,
+        discriminator: dynamic_bonding_curve::client::args::PartnerWithdrawSurplus::DISCRIMINATOR,
+        payload_len: 8,
+        number_of_accounts: 10,
+        receiver_index: 3,
+        receiver: ClaimedTokenReceiver::TokenVault,
+        fee_vault_index: 6,
+    },
+    WhitelistedAction {
+        program_id: dynamic_bonding_curve::ID,
+        discriminator: dynamic_bonding_curve::client::args::ClaimTradingFee::DISCRIMINATOR,
+        payload_len: 24, // max_amount_a: u64, max_amount_b: u64
+        number_of_accounts: 14,
+        receiver_index: 4,
+        receiver: ClaimedTokenReceiver::TokenVault,
+        fee_vault_index: 9,
+    },
+    WhitelistedAction {
+        program_id: dynamic_bonding_curve::ID,
+        discriminator: dynamic_bonding_curve::client::args::WithdrawMigrationFee::DISCRIMINATOR,
+        payload_len: 9, // flag: u8
+        number_of_accounts: 10,
+        receiver_index: 3,
+        receiver: ClaimedTokenReceiver::TokenVault,
+        fee_vault_index: 6,
+    },
+    WhitelistedAction {
+        program_id: dynamic_bonding_curve::ID,
+        discriminator: dynamic_bonding_curve::client::args::WithdrawLeftover::DISCRIMINATOR,
+        payload_len: 8,
+        number_of_accounts: 10,
+        receiver_index: 3,
+        receiver: ClaimedTokenReceiver::FeeVaultAta {
+            token_mint_index: 5,
+            token_program_index: 7,
+        },
+        fee_vault_index: 6, // leftover_receiver
+    },
 ];
@@ -34,4 +34,70 @@ pub enum FeeVaultError {
 
     #[msg("Invalid action")]
     InvalidAction,
+
+    #[msg("No active share")]
+    NoActiveShare,
+
+    #[msg("Duplicated user address")]
+    DuplicatedUser,
+
+    #[msg("Invalid payload length")]
+    InvalidPayloadLength,
+
+    #[msg("Invalid number of accounts")]
+    InvalidNumberOfAccounts,
+
+    #[msg("Invalid source program")]
+    InvalidSourceProgram,
+
+    #[msg("Fee vault is not in its expected signer position")]
+    InvalidFeeVaultPosition,
+
+    #[msg("Invalid swap input account")]
+    InvalidSwapInputAccount,
+
+    #[msg("Invalid admin")]
+    InvalidAdmin,
+
+    #[msg("Invalid protocol fee token account")]
+    InvalidProtocolFeeTokenAccount,
+
+    #[msg("Operation is paused")]
+    Paused,
+
+    #[msg("Fee vault is frozen")]
+    VaultFrozen,
+
+    #[msg("Transfer hook program is not whitelisted")]
+    TransferHookProgramNotWhitelisted,
+
+    #[msg("Invalid fee vault version")]
+    InvalidFeeVaultVersion,
+
+    #[msg("Invalid fee vault metadata")]
+    InvalidFeeVaultMetadata,
+
+    #[msg("Claimed amount is less than minimum")]
+    ClaimedAmountBelowMinimum,
+
+    #[msg("Token vault balance decreased")]
+    TokenVaultBalanceDecreased,
+
+    #[msg("Token vault authority changed")]
+    TokenVaultAuthorityChanged,
+
+    #[msg("Invalid treasury")]
+    InvalidTreasury,
+
+    #[msg("Invalid protocol fee bps")]
+    InvalidProtocolFeeBps,
+
+    #[msg("Invalid transfer hook programs")]
+    InvalidTransferHookPrograms,
+
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+
+    #[msg("Owner must sign to allow permanent delegate")]
+    PermanentDelegateNotApprovedByOwner,
 }
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::InitializeFeeVaultParameters;
+use crate::{FeeVaultMetadataParameters, InitializeFeeVaultParameters};
 
 #[event]
 pub struct EvtInitializeFeeVault {
@@ -15,15 +15,146 @@ pub struct EvtInitializeFeeVault {
 pub struct EvtFundFee {
     pub source_program: Pubkey,
     pub fee_vault: Pubkey,
-    pub funded_amount: u64,
+    pub token_mint: Pubkey,
+    pub funded_amount: u64, // excluding protocol fee and reserved transfer fee
+    pub protocol_fee: u64,
+    pub transfer_fee: u64, // token 2022 transfer fee withheld on the way into token vault
+    pub reserved_transfer_fee: u64, // set aside to carry transfer fee on claim
+    // post-state of fee vault
+    pub total_funded_fee: u64,
     pub fee_per_share: u128,
+    pub locked_fee: u64,
+    pub transfer_fee_reserve: u64,
+    pub total_share: u32,
+    pub vesting_end_timestamp: u64,
+    pub last_updated_timestamp: u64,
     pub payload: Vec<u8>,
 }
 
 #[event]
 pub struct EvtClaimFee {
     pub fee_vault: Pubkey,
+    pub token_mint: Pubkey,
     pub user: Pubkey,
     pub index: u8,
     pub claimed_fee: u64,
+    pub gross_amount: u64, // sent from token vault, claimed_fee plus transfer fee carried by vault
+    pub transfer_fee: u64, // token 2022 transfer fee withheld from gross_amount
+    pub net_amount: u64,   // received by user
+    // post-state of user
+    pub fee_claimed: u64,
+    pub fee_per_share_checkpoint: u128,
+    pub pending_fee: u64,
+    pub is_expired: u8,
+    // post-state of fee vault, claiming releases vested fee and expires shares too
+    pub fee_per_share: u128,
+    pub locked_fee: u64,
+    pub transfer_fee_reserve: u64,
+    pub total_share: u32,
+    pub vesting_end_timestamp: u64,
+    pub last_updated_timestamp: u64,
+}
+
+/// Share excluded from total_share once its end_timestamp is passed, emitted by the instruction
+/// that brings fee vault past end_timestamp
+#[event]
+pub struct EvtExpireShare {
+    pub fee_vault: Pubkey,
+    pub user: Pubkey,
+    pub index: u8,
+    // post-state of user
+    pub pending_fee: u64,
+    pub fee_per_share_checkpoint: u128,
+    // post-state of fee vault
+    pub total_share: u32,
+}
+
+#[event]
+pub struct EvtInitializeConfig {
+    pub config: Pubkey,
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+}
+
+#[event]
+pub struct EvtUpdateConfig {
+    pub config: Pubkey,
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+}
+
+#[event]
+pub struct EvtUpdateAdmin {
+    pub config: Pubkey,
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct EvtSetPauseFlags {
+    pub config: Pubkey,
+    pub pause_flags: u8,
+}
+
+#[event]
+pub struct EvtSetTransferHookPrograms {
+    pub config: Pubkey,
+    pub transfer_hook_programs: Vec<Pubkey>,
+}
+
+#[event]
+pub struct EvtFreezeVault {
+    pub fee_vault: Pubkey,
+    pub freeze_funding: bool,
+}
+
+#[event]
+pub struct EvtUnfreezeVault {
+    pub fee_vault: Pubkey,
+}
+
+#[event]
+pub struct EvtLockPosition {
+    pub fee_vault: Pubkey,
+    pub position: Pubkey,
+    pub vesting: Pubkey,
+    pub cliff_point: Option<u64>,
+    pub period_frequency: u64,
+    pub cliff_unlock_liquidity: u128,
+    pub liquidity_per_period: u128,
+    pub number_of_period: u16,
+}
+
+#[event]
+pub struct EvtPermanentLockPosition {
+    pub fee_vault: Pubkey,
+    pub position: Pubkey,
+    pub permanent_lock_liquidity: u128,
+}
+
+#[event]
+pub struct EvtRefreshVesting {
+    pub fee_vault: Pubkey,
+    pub position: Pubkey,
+}
+
+#[event]
+pub struct EvtMigrateFeeVault {
+    pub fee_vault: Pubkey,
+    pub version: u8,
+}
+
+#[event]
+pub struct EvtPullFromParent {
+    pub parent_fee_vault: Pubkey,
+    pub child_fee_vault: Pubkey,
+    pub index: u8,   // index of child fee vault in parent fee vault
+    pub amount: u64, // received by child fee vault
+}
+
+#[event]
+pub struct EvtSetFeeVaultMetadata {
+    pub fee_vault: Pubkey,
+    pub metadata: FeeVaultMetadataParameters,
 }
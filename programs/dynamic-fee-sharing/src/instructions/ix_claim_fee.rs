@@ -2,14 +2,23 @@ use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::const_pda;
+use crate::constants::seeds::CONFIG_PREFIX;
 use crate::event::EvtClaimFee;
-use crate::state::FeeVault;
-use crate::utils::token::transfer_from_fee_vault;
+use crate::math::SafeMath;
+use crate::state::{load_config, load_fee_vault, require_not_paused, FeeVault, PauseFlag};
+use crate::utils::event::{emit_event_cpi, emit_expired_shares};
+use crate::utils::token::{
+    calculate_transfer_fee_excluded_amount, calculate_transfer_fee_included_amount,
+    TokenVaultAccounts, TransferFeeExcludedAmount,
+};
 
-#[event_cpi]
 #[derive(Accounts)]
 pub struct ClaimFeeCtx<'info> {
-    #[account(mut, has_one = token_vault, has_one = token_mint)]
+    #[account(
+        mut,
+        constraint = load_fee_vault(&fee_vault)?.token_vault == token_vault.key() @ ErrorCode::ConstraintHasOne,
+        constraint = load_fee_vault(&fee_vault)?.token_mint == token_mint.key() @ ErrorCode::ConstraintHasOne
+    )]
     pub fee_vault: AccountLoader<'info, FeeVault>,
 
     /// CHECK: fee vault authority
@@ -29,29 +38,113 @@ pub struct ClaimFeeCtx<'info> {
     pub user: Signer<'info>,
 
     pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: protocol config, pause flags are not applied until it is initialized
+    #[account(
+        seeds = [
+            CONFIG_PREFIX,
+        ],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: event authority of self cpi, address is precomputed instead of deriving the bump
+    #[account(address = const_pda::event_authority::ID)]
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: self cpi fails if it is not this program
+    #[account(address = crate::ID)]
+    pub program: UncheckedAccount<'info>,
 }
 
-pub fn handle_claim_fee(ctx: Context<ClaimFeeCtx>, index: u8) -> Result<()> {
-    let mut fee_vault = ctx.accounts.fee_vault.load_mut()?;
-    let fee_being_claimed = fee_vault.validate_and_claim_fee(index, &ctx.accounts.user.key())?;
-
-    if fee_being_claimed > 0 {
-        transfer_from_fee_vault(
-            ctx.accounts.fee_vault_authority.to_account_info(),
-            &ctx.accounts.token_mint,
-            &ctx.accounts.token_vault,
-            &ctx.accounts.user_token_vault,
-            &ctx.accounts.token_program,
-            fee_being_claimed,
-        )?;
-
-        emit_cpi!(EvtClaimFee {
-            fee_vault: ctx.accounts.fee_vault.key(),
-            index,
-            user: ctx.accounts.user.key(),
-            claimed_fee: fee_being_claimed,
-        });
+pub fn handle_claim_fee<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, ClaimFeeCtx<'info>>,
+    index: u8,
+) -> Result<()> {
+    let event = claim_user_fee(
+        &ctx.accounts.fee_vault,
+        &ctx.accounts.config,
+        &TokenVaultAccounts {
+            fee_vault_authority: ctx.accounts.fee_vault_authority.to_account_info(),
+            token_mint: &ctx.accounts.token_mint,
+            token_vault: &ctx.accounts.token_vault,
+            token_program: &ctx.accounts.token_program,
+            remaining_accounts: ctx.remaining_accounts,
+        },
+        &ctx.accounts.user_token_vault,
+        &ctx.accounts.event_authority,
+        &ctx.accounts.user.key(),
+        index,
+    )?;
+
+    if let Some(event) = event {
+        emit_event_cpi(&ctx.accounts.event_authority, &event)?;
     }
 
     Ok(())
 }
+
+/// Pay fee accrued by user at index from token vault to user_token_vault, and return the event
+/// to be emitted if any fee is claimed. Shares expired by the claim are emitted right away
+pub fn claim_user_fee<'c: 'info, 'info>(
+    fee_vault: &AccountLoader<'info, FeeVault>,
+    config: &AccountInfo<'info>,
+    token_vault_accounts: &TokenVaultAccounts<'_, 'c, 'info>,
+    user_token_vault: &InterfaceAccount<'info, TokenAccount>,
+    event_authority: &AccountInfo<'info>,
+    user: &Pubkey,
+    index: u8,
+) -> Result<Option<EvtClaimFee>> {
+    let config = load_config(config)?;
+    require_not_paused(config.as_ref(), PauseFlag::Claim)?;
+
+    let fee_vault_key = fee_vault.key();
+    let mut fee_vault = fee_vault.load_mut()?;
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    let expired_flags = fee_vault.expired_flags();
+    let fee_being_claimed = fee_vault.validate_and_claim_fee(index, user, current_timestamp)?;
+    emit_expired_shares(event_authority, fee_vault_key, &fee_vault, expired_flags)?;
+
+    let token_mint = token_vault_accounts.token_mint;
+    let carried_transfer_fee = if fee_vault.is_transfer_fee_carried() {
+        let transfer_fee =
+            calculate_transfer_fee_included_amount(token_mint, fee_being_claimed)?.transfer_fee;
+        fee_vault.carry_transfer_fee(transfer_fee)?
+    } else {
+        0
+    };
+    let gross_amount = fee_being_claimed.safe_add(carried_transfer_fee)?;
+
+    if gross_amount == 0 {
+        return Ok(None);
+    }
+
+    token_vault_accounts.transfer_to(user_token_vault, gross_amount)?;
+
+    let TransferFeeExcludedAmount {
+        amount: net_amount,
+        transfer_fee,
+    } = calculate_transfer_fee_excluded_amount(token_mint, gross_amount)?;
+    let user_fee = &fee_vault.users[usize::from(index)];
+
+    Ok(Some(EvtClaimFee {
+        fee_vault: fee_vault_key,
+        token_mint: token_mint.key(),
+        index,
+        user: *user,
+        claimed_fee: fee_being_claimed,
+        gross_amount,
+        transfer_fee,
+        net_amount,
+        fee_claimed: user_fee.fee_claimed,
+        fee_per_share_checkpoint: user_fee.fee_per_share_checkpoint,
+        pending_fee: user_fee.pending_fee,
+        is_expired: user_fee.is_expired,
+        fee_per_share: fee_vault.fee_per_share,
+        locked_fee: fee_vault.locked_fee,
+        transfer_fee_reserve: fee_vault.transfer_fee_reserve,
+        total_share: fee_vault.total_share,
+        vesting_end_timestamp: fee_vault.vesting_end_timestamp,
+        last_updated_timestamp: fee_vault.last_updated_timestamp,
+    }))
+}
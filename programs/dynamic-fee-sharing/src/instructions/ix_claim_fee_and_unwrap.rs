@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{close_account, spl_token::native_mint, CloseAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::const_pda;
+use crate::constants::seeds::{CONFIG_PREFIX, TEMP_WSOL_PREFIX};
+use crate::instructions::claim_user_fee;
+use crate::state::{load_fee_vault, FeeVault};
+use crate::utils::event::emit_event_cpi;
+use crate::utils::token::TokenVaultAccounts;
+
+#[derive(Accounts)]
+pub struct ClaimFeeAndUnwrapCtx<'info> {
+    #[account(
+        mut,
+        constraint = load_fee_vault(&fee_vault)?.token_vault == token_vault.key() @ ErrorCode::ConstraintHasOne,
+        constraint = load_fee_vault(&fee_vault)?.token_mint == token_mint.key() @ ErrorCode::ConstraintHasOne
+    )]
+    pub fee_vault: AccountLoader<'info, FeeVault>,
+
+    /// CHECK: fee vault authority
+    #[account(
+        address = const_pda::fee_vault_authority::ID
+    )]
+    pub fee_vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = native_mint::ID
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// temporary wsol account, closed to user after claiming
+    #[account(
+        init,
+        seeds = [
+            TEMP_WSOL_PREFIX,
+            fee_vault.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        token::mint = token_mint,
+        token::authority = fee_vault_authority,
+        token::token_program = token_program,
+        payer = user,
+        bump,
+    )]
+    pub temp_wsol_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: protocol config, pause flags are not applied until it is initialized
+    #[account(
+        seeds = [
+            CONFIG_PREFIX,
+        ],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: event authority of self cpi, address is precomputed instead of deriving the bump
+    #[account(address = const_pda::event_authority::ID)]
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: self cpi fails if it is not this program
+    #[account(address = crate::ID)]
+    pub program: UncheckedAccount<'info>,
+}
+
+/// Claim fee of a native mint fee vault, and unwrap it to user's system account
+pub fn handle_claim_fee_and_unwrap<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, ClaimFeeAndUnwrapCtx<'info>>,
+    index: u8,
+) -> Result<()> {
+    let event = claim_user_fee(
+        &ctx.accounts.fee_vault,
+        &ctx.accounts.config,
+        &TokenVaultAccounts {
+            fee_vault_authority: ctx.accounts.fee_vault_authority.to_account_info(),
+            token_mint: &ctx.accounts.token_mint,
+            token_vault: &ctx.accounts.token_vault,
+            token_program: &ctx.accounts.token_program,
+            remaining_accounts: ctx.remaining_accounts,
+        },
+        &ctx.accounts.temp_wsol_account,
+        &ctx.accounts.event_authority,
+        &ctx.accounts.user.key(),
+        index,
+    )?;
+
+    // closing native token account pays both wrapped lamports and rent to user
+    let signer_seeds = fee_vault_authority_seeds!();
+    close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.temp_wsol_account.to_account_info(),
+            destination: ctx.accounts.user.to_account_info(),
+            authority: ctx.accounts.fee_vault_authority.to_account_info(),
+        },
+        &[&signer_seeds[..]],
+    ))?;
+
+    if let Some(event) = event {
+        emit_event_cpi(&ctx.accounts.event_authority, &event)?;
+    }
+
+    Ok(())
+}
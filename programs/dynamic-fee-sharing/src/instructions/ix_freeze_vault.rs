@@ -0,0 +1,38 @@
+use crate::event::{EvtFreezeVault, EvtUnfreezeVault};
+use crate::state::{load_fee_vault, FeeVault};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FreezeVaultCtx<'info> {
+    #[account(
+        mut,
+        constraint = load_fee_vault(&fee_vault)?.owner == owner.key() @ ErrorCode::ConstraintHasOne
+    )]
+    pub fee_vault: AccountLoader<'info, FeeVault>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn handle_freeze_vault(ctx: Context<FreezeVaultCtx>, freeze_funding: bool) -> Result<()> {
+    let mut fee_vault = ctx.accounts.fee_vault.load_mut()?;
+    fee_vault.freeze(freeze_funding);
+
+    emit_cpi!(EvtFreezeVault {
+        fee_vault: ctx.accounts.fee_vault.key(),
+        freeze_funding,
+    });
+
+    Ok(())
+}
+
+pub fn handle_unfreeze_vault(ctx: Context<FreezeVaultCtx>) -> Result<()> {
+    let mut fee_vault = ctx.accounts.fee_vault.load_mut()?;
+    fee_vault.unfreeze();
+
+    emit_cpi!(EvtUnfreezeVault {
+        fee_vault: ctx.accounts.fee_vault.key(),
+    });
+
+    Ok(())
+}
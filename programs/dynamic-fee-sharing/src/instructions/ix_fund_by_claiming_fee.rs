@@ -1,15 +1,33 @@
-use crate::constants::WHITELISTED_ACTIONS;
+use crate::const_pda;
+use crate::constants::{
+    seeds::CONFIG_PREFIX, ClaimedTokenReceiver, WhitelistedAction, DISCRIMINATOR_LEN,
+    WHITELISTED_ACTIONS,
+};
 use crate::event::EvtFundFee;
-use crate::state::FeeVault;
+use crate::state::{load_config, load_fee_vault, require_not_paused, FeeVault, PauseFlag};
+use crate::utils::event::{emit_event_cpi, emit_expired_shares};
+use crate::utils::protocol_fee::collect_protocol_fee;
+use crate::utils::token::{
+    calculate_transfer_fee_excluded_amount, get_withheld_transfer_fee, FeeVaultAtaAccounts,
+    TokenVaultAccounts,
+};
 use crate::{error::FeeVaultError, math::SafeMath};
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
 use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
-use anchor_spl::token_interface::TokenAccount;
+use anchor_lang::Discriminator;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token::Token;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-#[event_cpi]
 #[derive(Accounts)]
 pub struct FundByClaimingFeeCtx<'info> {
-    #[account(mut, has_one = token_vault)]
+    #[account(
+        mut,
+        constraint = load_fee_vault(&fee_vault)?.token_vault == token_vault.key() @ ErrorCode::ConstraintHasOne,
+        constraint = load_fee_vault(&fee_vault)?.token_mint == token_mint.key() @ ErrorCode::ConstraintHasOne
+    )]
     pub fee_vault: AccountLoader<'info, FeeVault>,
 
     #[account(mut)]
@@ -20,38 +38,248 @@ pub struct FundByClaimingFeeCtx<'info> {
 
     /// CHECK:: source program
     pub source_program: UncheckedAccount<'info>,
+
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: protocol config, protocol fee and pause flags are not applied until it is initialized
+    #[account(
+        seeds = [
+            CONFIG_PREFIX,
+        ],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: fee vault authority
+    #[account(
+        address = const_pda::fee_vault_authority::ID
+    )]
+    pub fee_vault_authority: UncheckedAccount<'info>,
+
+    /// token account of treasury, required when protocol fee is charged
+    #[account(mut)]
+    pub protocol_fee_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: event authority of self cpi, address is precomputed instead of deriving the bump
+    #[account(address = const_pda::event_authority::ID)]
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: self cpi fails if it is not this program
+    #[account(address = crate::ID)]
+    pub program: UncheckedAccount<'info>,
 }
 
-pub fn is_support_action<'info>(
+pub fn get_whitelisted_action(
     source_program: &Pubkey,
     discriminator: &[u8],
-    token_vault: Pubkey,
-    remaining_accounts: &[AccountInfo<'info>],
-) -> bool {
-    for &(program, disc, token_vault_index) in WHITELISTED_ACTIONS.iter() {
-        if program.eq(source_program) && disc.eq(discriminator) {
-            if let Some(token_vault_account) = remaining_accounts.get(token_vault_index) {
-                return token_vault.eq(token_vault_account.key);
+) -> Option<&'static WhitelistedAction> {
+    WHITELISTED_ACTIONS.iter().find(|action| {
+        action.program_id.eq(source_program) && action.discriminator.eq(discriminator)
+    })
+}
+
+impl WhitelistedAction {
+    pub fn is_claim_position_fee(&self) -> bool {
+        self.program_id.eq(&damm_v2::ID)
+            && self
+                .discriminator
+                .eq(damm_v2::client::args::ClaimPositionFee::DISCRIMINATOR)
+    }
+
+    pub fn validate(
+        &self,
+        payload: &[u8],
+        fee_vault: &Pubkey,
+        token_vault: &Pubkey,
+        token_mint: &Pubkey,
+        remaining_accounts: &[AccountInfo],
+    ) -> Result<()> {
+        require!(
+            payload.len() == self.payload_len,
+            FeeVaultError::InvalidPayloadLength
+        );
+        require!(
+            remaining_accounts.len() == self.number_of_accounts,
+            FeeVaultError::InvalidNumberOfAccounts
+        );
+
+        let receiver = remaining_accounts[self.receiver_index].key;
+        match self.receiver {
+            ClaimedTokenReceiver::TokenVault => {
+                require!(receiver.eq(token_vault), FeeVaultError::InvalidAction);
+            }
+            ClaimedTokenReceiver::FeeVaultAta {
+                token_mint_index,
+                token_program_index,
+            } => {
+                let token_program = remaining_accounts[token_program_index].key;
+                require!(
+                    remaining_accounts[token_mint_index].key.eq(token_mint)
+                        && (token_program.eq(&Token::id()) || token_program.eq(&Token2022::id())),
+                    FeeVaultError::InvalidAction
+                );
+                let fee_vault_ata = get_associated_token_address_with_program_id(
+                    fee_vault,
+                    token_mint,
+                    token_program,
+                );
+                require!(receiver.eq(&fee_vault_ata), FeeVaultError::InvalidAction);
             }
         }
+
+        // fee vault signs the cpi, so it must only appear in the slot of the claimer
+        for (index, account) in remaining_accounts.iter().enumerate() {
+            require!(
+                account.key.eq(fee_vault) == (index == self.fee_vault_index),
+                FeeVaultError::InvalidFeeVaultPosition
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Build cpi instruction to source program, fee vault only signs for its own slot. Payload is
+    /// moved into instruction data, so it can be taken back after invoking without a copy
+    pub fn to_instruction(
+        &self,
+        payload: Vec<u8>,
+        remaining_accounts: &[AccountInfo],
+    ) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: remaining_accounts
+                .iter()
+                .enumerate()
+                .map(|(index, account)| AccountMeta {
+                    pubkey: *account.key,
+                    is_signer: index == self.fee_vault_index,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: payload,
+        }
+    }
+}
+
+/// Token vault state before source program cpi. Source program is only allowed to add to token
+/// vault, even though it receives token vault as a writable account
+pub struct TokenVaultSnapshot {
+    pub amount: u64,
+    pub owner: Pubkey,
+    pub delegate: COption<Pubkey>,
+    pub close_authority: COption<Pubkey>,
+}
+
+impl TokenVaultSnapshot {
+    pub fn new(token_vault: &TokenAccount) -> Self {
+        Self {
+            amount: token_vault.amount,
+            owner: token_vault.owner,
+            delegate: token_vault.delegate,
+            close_authority: token_vault.close_authority,
+        }
     }
-    false
+
+    /// Validate token vault after cpi and return the amount claimed into it
+    pub fn get_claimed_amount(&self, token_vault: &TokenAccount) -> Result<u64> {
+        require!(
+            token_vault.owner.eq(&self.owner)
+                && token_vault.delegate.eq(&self.delegate)
+                && token_vault.close_authority.eq(&self.close_authority),
+            FeeVaultError::TokenVaultAuthorityChanged
+        );
+        require!(
+            token_vault.amount >= self.amount,
+            FeeVaultError::TokenVaultBalanceDecreased
+        );
+        Ok(token_vault.amount.safe_sub(self.amount)?)
+    }
+}
+
+// claim_position_fee pays token a to index 3 and token b to token vault, so the claimed token a
+// is swapped into vault mint with the same pool
+pub mod claim_position_fee_accounts {
+    pub const POOL_AUTHORITY: usize = 0;
+    pub const POOL: usize = 1;
+    pub const TOKEN_A_ACCOUNT: usize = 3;
+    pub const TOKEN_B_ACCOUNT: usize = 4;
+    pub const TOKEN_A_VAULT: usize = 5;
+    pub const TOKEN_B_VAULT: usize = 6;
+    pub const TOKEN_A_MINT: usize = 7;
+    pub const TOKEN_B_MINT: usize = 8;
+    pub const OWNER: usize = 10;
+    pub const TOKEN_A_PROGRAM: usize = 11;
+    pub const TOKEN_B_PROGRAM: usize = 12;
+    pub const EVENT_AUTHORITY: usize = 13;
+    pub const PROGRAM: usize = 14;
+}
+
+fn swap_claimed_token(
+    remaining_accounts: &[AccountInfo<'_>],
+    signer_seeds: &[&[u8]],
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    use claim_position_fee_accounts::*;
+    damm_v2::cpi::swap(
+        CpiContext::new_with_signer(
+            remaining_accounts[PROGRAM].clone(),
+            damm_v2::cpi::accounts::Swap {
+                pool_authority: remaining_accounts[POOL_AUTHORITY].clone(),
+                pool: remaining_accounts[POOL].clone(),
+                input_token_account: remaining_accounts[TOKEN_A_ACCOUNT].clone(),
+                output_token_account: remaining_accounts[TOKEN_B_ACCOUNT].clone(),
+                token_a_vault: remaining_accounts[TOKEN_A_VAULT].clone(),
+                token_b_vault: remaining_accounts[TOKEN_B_VAULT].clone(),
+                token_a_mint: remaining_accounts[TOKEN_A_MINT].clone(),
+                token_b_mint: remaining_accounts[TOKEN_B_MINT].clone(),
+                payer: remaining_accounts[OWNER].clone(),
+                token_a_program: remaining_accounts[TOKEN_A_PROGRAM].clone(),
+                token_b_program: remaining_accounts[TOKEN_B_PROGRAM].clone(),
+                referral_token_account: None,
+                event_authority: remaining_accounts[EVENT_AUTHORITY].clone(),
+                program: remaining_accounts[PROGRAM].clone(),
+            },
+            &[signer_seeds],
+        ),
+        damm_v2::types::SwapParameters {
+            amount_in,
+            minimum_amount_out,
+        },
+    )
 }
 
-pub fn handle_fund_by_claiming_fee(
-    ctx: Context<FundByClaimingFeeCtx>,
+/// `minimum_amount_out` is only set for claim_position_fee, to swap claimed token a into vault mint.
+/// `min_claimed_amount` rejects the claim when token vault receives less than it
+pub fn handle_fund_by_claiming_fee<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, FundByClaimingFeeCtx<'info>>,
     payload: Vec<u8>,
+    minimum_amount_out: Option<u64>,
+    min_claimed_amount: Option<u64>,
 ) -> Result<()> {
-    let discriminator = &payload[..8]; // first 8 bytes is discriminator
+    let config = load_config(&ctx.accounts.config)?;
+    require_not_paused(config.as_ref(), PauseFlag::FundByClaiming)?;
+
     require!(
-        is_support_action(
-            ctx.accounts.source_program.key,
-            discriminator,
-            ctx.accounts.token_vault.key(),
-            ctx.remaining_accounts
-        ),
-        FeeVaultError::InvalidAction
+        payload.len() >= DISCRIMINATOR_LEN,
+        FeeVaultError::InvalidPayloadLength
     );
+    let discriminator = &payload[..DISCRIMINATOR_LEN];
+    let action = get_whitelisted_action(ctx.accounts.source_program.key, discriminator)
+        .ok_or(FeeVaultError::InvalidAction)?;
+    require!(
+        ctx.accounts.source_program.executable,
+        FeeVaultError::InvalidSourceProgram
+    );
+    action.validate(
+        &payload,
+        &ctx.accounts.fee_vault.key(),
+        &ctx.accounts.token_vault.key(),
+        &ctx.accounts.fee_vault.load()?.token_mint,
+        ctx.remaining_accounts,
+    )?;
 
     let fee_vault = ctx.accounts.fee_vault.load()?;
 
@@ -66,60 +294,153 @@ pub fn handle_fund_by_claiming_fee(
         FeeVaultError::InvalidFeeVault
     );
 
-    let before_token_vault_balance = ctx.accounts.token_vault.amount;
-
-    let accounts: Vec<AccountMeta> = ctx
-        .remaining_accounts
-        .iter()
-        .map(|acc| {
-            let is_signer = acc.key == &ctx.accounts.fee_vault.key();
-            AccountMeta {
-                pubkey: *acc.key,
-                is_signer: is_signer,
-                is_writable: acc.is_writable,
-            }
-        })
-        .collect();
-
-    let account_infos: Vec<AccountInfo> = ctx
-        .remaining_accounts
-        .iter()
-        .map(|acc| AccountInfo { ..acc.clone() })
-        .collect();
-    // invoke instruction to amm
+    let token_vault_snapshot = TokenVaultSnapshot::new(&ctx.accounts.token_vault);
+    let before_withheld_transfer_fee =
+        get_withheld_transfer_fee(&ctx.accounts.token_vault.to_account_info())?;
+
+    let swap_input_account = if minimum_amount_out.is_some() {
+        require!(action.is_claim_position_fee(), FeeVaultError::InvalidAction);
+        let input_account = InterfaceAccount::<TokenAccount>::try_from(
+            &ctx.remaining_accounts[claim_position_fee_accounts::TOKEN_A_ACCOUNT],
+        )?;
+        // fee vault pays the swap, so it must own the account receiving token a
+        require!(
+            input_account.owner.eq(&ctx.accounts.fee_vault.key())
+                && input_account
+                    .mint
+                    .eq(ctx.remaining_accounts[claim_position_fee_accounts::TOKEN_A_MINT].key),
+            FeeVaultError::InvalidSwapInputAccount
+        );
+        Some(input_account)
+    } else {
+        None
+    };
+
     let base = fee_vault.base;
     let token_mint = fee_vault.token_mint;
     let fee_vault_bump = fee_vault.fee_vault_bump;
     let signer_seeds = fee_vault_seeds!(base, token_mint, fee_vault_bump);
     drop(fee_vault);
 
-    invoke_signed(
-        &Instruction {
-            program_id: ctx.accounts.source_program.key(),
-            accounts,
-            data: payload.clone(),
-        },
-        &account_infos,
-        &[signer_seeds],
-    )?;
+    // invoke instruction to amm, remaining accounts are passed as is instead of cloned
+    let instruction = action.to_instruction(payload, ctx.remaining_accounts);
+    invoke_signed(&instruction, ctx.remaining_accounts, &[signer_seeds])?;
+    let payload = instruction.data;
 
-    ctx.accounts.token_vault.reload()?;
+    if let (Some(mut input_account), Some(minimum_amount_out)) =
+        (swap_input_account, minimum_amount_out)
+    {
+        let before_input_balance = input_account.amount;
+        input_account.reload()?;
+        let claimed_input_amount = input_account.amount.safe_sub(before_input_balance)?;
+        if claimed_input_amount > 0 {
+            swap_claimed_token(
+                ctx.remaining_accounts,
+                signer_seeds,
+                claimed_input_amount,
+                minimum_amount_out,
+            )?;
+        }
+    }
+
+    if let ClaimedTokenReceiver::FeeVaultAta {
+        token_mint_index,
+        token_program_index,
+    } = action.receiver
+    {
+        let fee_vault_ata = InterfaceAccount::<TokenAccount>::try_from(
+            &ctx.remaining_accounts[action.receiver_index],
+        )?;
+        let claimed_token_mint =
+            InterfaceAccount::<Mint>::try_from(&ctx.remaining_accounts[token_mint_index])?;
+
+        if fee_vault_ata.amount > 0 {
+            FeeVaultAtaAccounts {
+                fee_vault: ctx.accounts.fee_vault.to_account_info(),
+                token_mint: &claimed_token_mint,
+                fee_vault_ata: &fee_vault_ata,
+                token_program: &ctx.remaining_accounts[token_program_index],
+                remaining_accounts: &[],
+            }
+            .transfer_to(
+                signer_seeds,
+                &ctx.accounts.token_vault,
+                fee_vault_ata.amount,
+            )?;
+        }
+    }
 
-    let after_token_vault_balance = ctx.accounts.token_vault.amount;
+    ctx.accounts.token_vault.reload()?;
 
-    let claimed_amount = after_token_vault_balance.safe_sub(before_token_vault_balance)?;
+    let claimed_amount = token_vault_snapshot.get_claimed_amount(&ctx.accounts.token_vault)?;
+    if let Some(min_claimed_amount) = min_claimed_amount {
+        require!(
+            claimed_amount >= min_claimed_amount,
+            FeeVaultError::ClaimedAmountBelowMinimum
+        );
+    }
+    // transfer fee is withheld in token vault by token 2022 when source program pays to it
+    let transfer_fee = get_withheld_transfer_fee(&ctx.accounts.token_vault.to_account_info())?
+        .safe_sub(before_withheld_transfer_fee)?;
 
     if claimed_amount > 0 {
+        let protocol_fee = collect_protocol_fee(
+            config.as_ref(),
+            ctx.accounts.protocol_fee_token_account.as_deref(),
+            &TokenVaultAccounts {
+                fee_vault_authority: ctx.accounts.fee_vault_authority.to_account_info(),
+                token_mint: &ctx.accounts.token_mint,
+                token_vault: &ctx.accounts.token_vault,
+                token_program: &ctx.accounts.token_program,
+                remaining_accounts: &[], // whitelisted source programs don't support transfer hook mints
+            },
+            claimed_amount,
+        )?;
+        let amount_after_protocol_fee = claimed_amount.safe_sub(protocol_fee)?;
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
         let mut fee_vault = ctx.accounts.fee_vault.load_mut()?;
-        fee_vault.fund_fee(claimed_amount)?;
-
-        emit_cpi!(EvtFundFee {
-            source_program: ctx.accounts.source_program.key(),
-            fee_vault: ctx.accounts.fee_vault.key(),
-            payload,
-            funded_amount: claimed_amount,
-            fee_per_share: fee_vault.fee_per_share,
-        });
+        let reserved_transfer_fee = if fee_vault.is_transfer_fee_carried() {
+            calculate_transfer_fee_excluded_amount(
+                &ctx.accounts.token_mint,
+                amount_after_protocol_fee,
+            )?
+            .transfer_fee
+        } else {
+            0
+        };
+        let funded_amount = amount_after_protocol_fee.safe_sub(reserved_transfer_fee)?;
+        let expired_flags = fee_vault.expired_flags();
+        fee_vault.fund_fee(funded_amount, current_timestamp)?;
+        fee_vault.reserve_transfer_fee(reserved_transfer_fee)?;
+
+        emit_expired_shares(
+            &ctx.accounts.event_authority,
+            ctx.accounts.fee_vault.key(),
+            &fee_vault,
+            expired_flags,
+        )?;
+
+        emit_event_cpi(
+            &ctx.accounts.event_authority,
+            &EvtFundFee {
+                source_program: ctx.accounts.source_program.key(),
+                fee_vault: ctx.accounts.fee_vault.key(),
+                token_mint: ctx.accounts.token_mint.key(),
+                payload,
+                funded_amount,
+                protocol_fee,
+                transfer_fee,
+                reserved_transfer_fee,
+                total_funded_fee: fee_vault.total_funded_fee,
+                fee_per_share: fee_vault.fee_per_share,
+                locked_fee: fee_vault.locked_fee,
+                transfer_fee_reserve: fee_vault.transfer_fee_reserve,
+                total_share: fee_vault.total_share,
+                vesting_end_timestamp: fee_vault.vesting_end_timestamp,
+                last_updated_timestamp: fee_vault.last_updated_timestamp,
+            },
+        )?;
     }
     Ok(())
 }
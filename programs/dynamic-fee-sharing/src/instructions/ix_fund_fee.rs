@@ -1,15 +1,26 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
+use crate::const_pda;
+use crate::constants::seeds::CONFIG_PREFIX;
 use crate::error::FeeVaultError;
 use crate::event::EvtFundFee;
-use crate::state::FeeVault;
-use crate::utils::token::{calculate_transfer_fee_excluded_amount, transfer_from_user};
+use crate::math::SafeMath;
+use crate::state::{load_config, load_fee_vault, require_not_paused, FeeVault, PauseFlag};
+use crate::utils::event::{emit_event_cpi, emit_expired_shares};
+use crate::utils::protocol_fee::collect_protocol_fee;
+use crate::utils::token::{
+    calculate_transfer_fee_excluded_amount, require_whitelisted_transfer_hook, transfer_from_user,
+    TokenVaultAccounts,
+};
 
-#[event_cpi]
 #[derive(Accounts)]
 pub struct FundFeeCtx<'info> {
-    #[account(mut, has_one = token_vault, has_one = token_mint)]
+    #[account(
+        mut,
+        constraint = load_fee_vault(&fee_vault)?.token_vault == token_vault.key() @ ErrorCode::ConstraintHasOne,
+        constraint = load_fee_vault(&fee_vault)?.token_mint == token_mint.key() @ ErrorCode::ConstraintHasOne
+    )]
     pub fee_vault: AccountLoader<'info, FeeVault>,
 
     #[account(mut)]
@@ -23,18 +34,49 @@ pub struct FundFeeCtx<'info> {
     pub funder: Signer<'info>,
 
     pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: protocol config, protocol fee and pause flags are not applied until it is initialized
+    #[account(
+        seeds = [
+            CONFIG_PREFIX,
+        ],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: fee vault authority
+    #[account(
+        address = const_pda::fee_vault_authority::ID
+    )]
+    pub fee_vault_authority: UncheckedAccount<'info>,
+
+    /// token account of treasury, required when protocol fee is charged
+    #[account(mut)]
+    pub protocol_fee_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: event authority of self cpi, address is precomputed instead of deriving the bump
+    #[account(address = const_pda::event_authority::ID)]
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: self cpi fails if it is not this program
+    #[account(address = crate::ID)]
+    pub program: UncheckedAccount<'info>,
 }
 
-pub fn handle_fund_fee(ctx: Context<FundFeeCtx>, max_amount: u64) -> Result<()> {
+pub fn handle_fund_fee<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, FundFeeCtx<'info>>,
+    max_amount: u64,
+) -> Result<()> {
+    let config = load_config(&ctx.accounts.config)?;
+    require_not_paused(config.as_ref(), PauseFlag::Fund)?;
+    require_whitelisted_transfer_hook(&ctx.accounts.token_mint, config.as_ref())?;
+
     let amount = max_amount.min(ctx.accounts.fund_token_vault.amount);
     require!(amount > 0, FeeVaultError::AmountIsZero);
 
     // transfer token
     let excluded_transfer_fee_amount =
-        calculate_transfer_fee_excluded_amount(&ctx.accounts.token_mint, amount)?.amount;
-
-    let mut fee_vault = ctx.accounts.fee_vault.load_mut()?;
-    fee_vault.fund_fee(excluded_transfer_fee_amount)?;
+        calculate_transfer_fee_excluded_amount(&ctx.accounts.token_mint, amount)?;
 
     transfer_from_user(
         &ctx.accounts.funder,
@@ -42,16 +84,64 @@ pub fn handle_fund_fee(ctx: Context<FundFeeCtx>, max_amount: u64) -> Result<()>
         &ctx.accounts.fund_token_vault,
         &ctx.accounts.token_vault,
         &ctx.accounts.token_program,
+        ctx.remaining_accounts,
         amount,
     )?;
 
-    emit_cpi!(EvtFundFee {
-        source_program: Pubkey::default(),
-        fee_vault: ctx.accounts.fee_vault.key(),
-        payload: vec![],
-        funded_amount: excluded_transfer_fee_amount,
-        fee_per_share: fee_vault.fee_per_share
-    });
+    let protocol_fee = collect_protocol_fee(
+        config.as_ref(),
+        ctx.accounts.protocol_fee_token_account.as_deref(),
+        &TokenVaultAccounts {
+            fee_vault_authority: ctx.accounts.fee_vault_authority.to_account_info(),
+            token_mint: &ctx.accounts.token_mint,
+            token_vault: &ctx.accounts.token_vault,
+            token_program: &ctx.accounts.token_program,
+            remaining_accounts: ctx.remaining_accounts,
+        },
+        excluded_transfer_fee_amount.amount,
+    )?;
+    let amount_after_protocol_fee = excluded_transfer_fee_amount.amount.safe_sub(protocol_fee)?;
+
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    let mut fee_vault = ctx.accounts.fee_vault.load_mut()?;
+    let reserved_transfer_fee = if fee_vault.is_transfer_fee_carried() {
+        calculate_transfer_fee_excluded_amount(&ctx.accounts.token_mint, amount_after_protocol_fee)?
+            .transfer_fee
+    } else {
+        0
+    };
+    let funded_amount = amount_after_protocol_fee.safe_sub(reserved_transfer_fee)?;
+    let expired_flags = fee_vault.expired_flags();
+    fee_vault.fund_fee(funded_amount, current_timestamp)?;
+    fee_vault.reserve_transfer_fee(reserved_transfer_fee)?;
+
+    emit_expired_shares(
+        &ctx.accounts.event_authority,
+        ctx.accounts.fee_vault.key(),
+        &fee_vault,
+        expired_flags,
+    )?;
+
+    emit_event_cpi(
+        &ctx.accounts.event_authority,
+        &EvtFundFee {
+            source_program: Pubkey::default(),
+            fee_vault: ctx.accounts.fee_vault.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            payload: vec![],
+            funded_amount,
+            protocol_fee,
+            transfer_fee: excluded_transfer_fee_amount.transfer_fee,
+            reserved_transfer_fee,
+            total_funded_fee: fee_vault.total_funded_fee,
+            fee_per_share: fee_vault.fee_per_share,
+            locked_fee: fee_vault.locked_fee,
+            transfer_fee_reserve: fee_vault.transfer_fee_reserve,
+            total_share: fee_vault.total_share,
+            vesting_end_timestamp: fee_vault.vesting_end_timestamp,
+            last_updated_timestamp: fee_vault.last_updated_timestamp,
+        },
+    )?;
 
     Ok(())
 }
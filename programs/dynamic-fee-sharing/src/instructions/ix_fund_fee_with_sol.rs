@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token::{spl_token::native_mint, sync_native, SyncNative};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::const_pda;
+use crate::constants::seeds::CONFIG_PREFIX;
+use crate::error::FeeVaultError;
+use crate::event::EvtFundFee;
+use crate::math::SafeMath;
+use crate::state::{load_config, load_fee_vault, require_not_paused, FeeVault, PauseFlag};
+use crate::utils::event::{emit_event_cpi, emit_expired_shares};
+use crate::utils::protocol_fee::collect_protocol_fee;
+use crate::utils::token::TokenVaultAccounts;
+
+#[derive(Accounts)]
+pub struct FundFeeWithSolCtx<'info> {
+    #[account(
+        mut,
+        constraint = load_fee_vault(&fee_vault)?.token_vault == token_vault.key() @ ErrorCode::ConstraintHasOne,
+        constraint = load_fee_vault(&fee_vault)?.token_mint == token_mint.key() @ ErrorCode::ConstraintHasOne
+    )]
+    pub fee_vault: AccountLoader<'info, FeeVault>,
+
+    #[account(mut)]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = native_mint::ID
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: protocol config, protocol fee and pause flags are not applied until it is initialized
+    #[account(
+        seeds = [
+            CONFIG_PREFIX,
+        ],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: fee vault authority
+    #[account(
+        address = const_pda::fee_vault_authority::ID
+    )]
+    pub fee_vault_authority: UncheckedAccount<'info>,
+
+    /// token account of treasury, required when protocol fee is charged
+    #[account(mut)]
+    pub protocol_fee_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: event authority of self cpi, address is precomputed instead of deriving the bump
+    #[account(address = const_pda::event_authority::ID)]
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: self cpi fails if it is not this program
+    #[account(address = crate::ID)]
+    pub program: UncheckedAccount<'info>,
+}
+
+/// Fund lamports into a fee vault of native mint, lamports are wrapped in token vault
+pub fn handle_fund_fee_with_sol(ctx: Context<FundFeeWithSolCtx>, amount: u64) -> Result<()> {
+    let config = load_config(&ctx.accounts.config)?;
+    require_not_paused(config.as_ref(), PauseFlag::Fund)?;
+    require!(amount > 0, FeeVaultError::AmountIsZero);
+
+    transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.token_vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    sync_native(CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        SyncNative {
+            account: ctx.accounts.token_vault.to_account_info(),
+        },
+    ))?;
+
+    // native mint has no transfer fee
+    let protocol_fee = collect_protocol_fee(
+        config.as_ref(),
+        ctx.accounts.protocol_fee_token_account.as_deref(),
+        &TokenVaultAccounts {
+            fee_vault_authority: ctx.accounts.fee_vault_authority.to_account_info(),
+            token_mint: &ctx.accounts.token_mint,
+            token_vault: &ctx.accounts.token_vault,
+            token_program: &ctx.accounts.token_program,
+            remaining_accounts: &[],
+        },
+        amount,
+    )?;
+    let funded_amount = amount.safe_sub(protocol_fee)?;
+
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    let mut fee_vault = ctx.accounts.fee_vault.load_mut()?;
+    let expired_flags = fee_vault.expired_flags();
+    fee_vault.fund_fee(funded_amount, current_timestamp)?;
+
+    emit_expired_shares(
+        &ctx.accounts.event_authority,
+        ctx.accounts.fee_vault.key(),
+        &fee_vault,
+        expired_flags,
+    )?;
+
+    emit_event_cpi(
+        &ctx.accounts.event_authority,
+        &EvtFundFee {
+            source_program: Pubkey::default(),
+            fee_vault: ctx.accounts.fee_vault.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            payload: vec![],
+            funded_amount,
+            protocol_fee,
+            transfer_fee: 0,
+            reserved_transfer_fee: 0,
+            total_funded_fee: fee_vault.total_funded_fee,
+            fee_per_share: fee_vault.fee_per_share,
+            locked_fee: fee_vault.locked_fee,
+            transfer_fee_reserve: fee_vault.transfer_fee_reserve,
+            total_share: fee_vault.total_share,
+            vesting_end_timestamp: fee_vault.vesting_end_timestamp,
+            last_updated_timestamp: fee_vault.last_updated_timestamp,
+        },
+    )?;
+
+    Ok(())
+}
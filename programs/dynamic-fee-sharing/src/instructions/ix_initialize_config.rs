@@ -0,0 +1,80 @@
+use crate::constants::{seeds::CONFIG_PREFIX, MAX_PROTOCOL_FEE_BPS};
+use crate::error::FeeVaultError;
+use crate::event::EvtInitializeConfig;
+use crate::state::Config;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct ConfigParameters {
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+}
+
+impl ConfigParameters {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.treasury.ne(&Pubkey::default()),
+            FeeVaultError::InvalidTreasury
+        );
+        require!(
+            self.protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS,
+            FeeVaultError::InvalidProtocolFeeBps
+        );
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeConfigCtx<'info> {
+    #[account(
+        init,
+        seeds = [
+            CONFIG_PREFIX,
+        ],
+        bump,
+        payer = admin,
+        space = 8 + Config::INIT_SPACE
+    )]
+    pub config: AccountLoader<'info, Config>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    // only upgrade authority of the program is able to initialize config
+    #[account(
+        seeds = [
+            crate::ID.as_ref(),
+        ],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ FeeVaultError::InvalidAdmin
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_config(
+    ctx: Context<InitializeConfigCtx>,
+    params: &ConfigParameters,
+) -> Result<()> {
+    params.validate()?;
+
+    let mut config = ctx.accounts.config.load_init()?;
+    config.initialize(
+        ctx.accounts.admin.key,
+        &params.treasury,
+        params.protocol_fee_bps,
+    );
+
+    emit_cpi!(EvtInitializeConfig {
+        config: ctx.accounts.config.key(),
+        admin: ctx.accounts.admin.key(),
+        treasury: params.treasury,
+        protocol_fee_bps: params.protocol_fee_bps,
+    });
+
+    Ok(())
+}
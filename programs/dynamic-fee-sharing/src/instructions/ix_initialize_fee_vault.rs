@@ -1,46 +1,115 @@
-use crate::constants::MAX_USER;
+use crate::constants::{MAX_USER, MAX_VESTING_DURATION};
 use crate::error::FeeVaultError;
 use crate::event::EvtInitializeFeeVault;
-use crate::state::FeeVaultType;
-use crate::utils::token::{get_token_program_flags, is_supported_mint};
+use crate::math::SafeMath;
+use crate::state::{
+    load_config, Config, FeeVaultKeys, FeeVaultMetadata, FeeVaultType, TransferFeeMode,
+};
+use crate::utils::token::{get_token_program_flags, is_supported_mint, MintExtensionPolicy};
+use crate::FeeVaultMetadataParameters;
 use crate::{
-    constants::seeds::{FEE_VAULT_AUTHORITY_PREFIX, TOKEN_VAULT_PREFIX},
+    constants::seeds::{
+        CONFIG_PREFIX, FEE_VAULT_AUTHORITY_PREFIX, FEE_VAULT_METADATA_PREFIX, TOKEN_VAULT_PREFIX,
+    },
     state::FeeVault,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+#[repr(u8)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    IntoPrimitive,
+    TryFromPrimitive,
+    AnchorDeserialize,
+    AnchorSerialize,
+)]
+pub enum DuplicateUserMode {
+    Reject,
+    Merge, // duplicated addresses are merged into one slot with summed share
+}
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct InitializeFeeVaultParameters {
-    pub padding: [u64; 8], // for future use
+    pub vesting_duration: u64, // duration to stream funded fee to share holders, 0 means no vesting
+    pub duplicate_user_mode: u8,
+    pub transfer_fee_mode: u8,
+    pub allow_permanent_delegate: u8, // opt in to mint with permanent delegate, which could drain token vault
+    pub padding_0: [u8; 5],
+    pub padding: [u64; 6], // for future use
     pub users: Vec<UserShare>,
+    pub metadata: Option<FeeVaultMetadataParameters>, // requires fee_vault_metadata account when set
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
 pub struct UserShare {
     pub address: Pubkey,
     pub share: u32,
+    pub end_timestamp: u64, // 0 means share never expires
 }
 
 impl InitializeFeeVaultParameters {
-    pub fn validate(&self) -> Result<()> {
-        let number_of_user = self.users.len();
+    /// Permanent delegate of the mint could drain token vault, so only owner is able to opt in
+    pub fn validate_owner_approval(&self, owner_is_signer: bool) -> Result<()> {
         require!(
-            number_of_user >= 2 && number_of_user <= MAX_USER,
-            FeeVaultError::ExceededUser
+            self.allow_permanent_delegate == 0 || owner_is_signer,
+            FeeVaultError::PermanentDelegateNotApprovedByOwner
+        );
+        Ok(())
+    }
+
+    /// Validate parameters and return the user shares to be stored in fee vault
+    pub fn validate(&self, current_timestamp: u64) -> Result<Vec<UserShare>> {
+        let duplicate_user_mode = DuplicateUserMode::try_from(self.duplicate_user_mode)
+            .map_err(|_| FeeVaultError::InvalidFeeVaultParameters)?;
+        TransferFeeMode::try_from(self.transfer_fee_mode)
+            .map_err(|_| FeeVaultError::InvalidFeeVaultParameters)?;
+        require!(
+            self.allow_permanent_delegate <= 1,
+            FeeVaultError::InvalidFeeVaultParameters
+        );
+        // vesting end is computed from funding timestamp, so it must not overflow on any funding
+        require!(
+            self.vesting_duration <= MAX_VESTING_DURATION,
+            FeeVaultError::InvalidFeeVaultParameters
         );
-        for i in 0..number_of_user {
+
+        let mut users: Vec<UserShare> = Vec::with_capacity(self.users.len());
+        for user in self.users.iter() {
+            require!(user.share > 0, FeeVaultError::InvalidFeeVaultParameters);
             require!(
-                self.users[i].share > 0,
-                FeeVaultError::InvalidFeeVaultParameters
+                user.address.ne(&Pubkey::default()),
+                FeeVaultError::InvalidUserAddress
             );
             require!(
-                self.users[i].address.ne(&Pubkey::default()),
-                FeeVaultError::InvalidUserAddress
+                user.end_timestamp == 0 || user.end_timestamp > current_timestamp,
+                FeeVaultError::InvalidFeeVaultParameters
             );
+
+            match users.iter_mut().find(|u| u.address.eq(&user.address)) {
+                Some(existing_user) => {
+                    require!(
+                        duplicate_user_mode == DuplicateUserMode::Merge
+                            && existing_user.end_timestamp == user.end_timestamp,
+                        FeeVaultError::DuplicatedUser
+                    );
+                    existing_user.share = existing_user.share.safe_add(user.share)?;
+                }
+                None => users.push(*user),
+            }
         }
-        // that is fine to leave user addresses are duplicated?
-        Ok(())
+
+        let number_of_user = users.len();
+        require!(
+            (2..=MAX_USER).contains(&number_of_user),
+            FeeVaultError::ExceededUser
+        );
+
+        Ok(users)
     }
 }
 
@@ -58,7 +127,7 @@ pub struct InitializeFeeVaultCtx<'info> {
     /// CHECK: pool authority
     #[account(
             seeds = [
-                FEE_VAULT_AUTHORITY_PREFIX.as_ref(),
+                FEE_VAULT_AUTHORITY_PREFIX,
             ],
             bump,
         )]
@@ -67,7 +136,7 @@ pub struct InitializeFeeVaultCtx<'info> {
     #[account(
         init,
         seeds = [
-            TOKEN_VAULT_PREFIX.as_ref(),
+            TOKEN_VAULT_PREFIX,
             fee_vault.key().as_ref(),
         ],
         token::mint = token_mint,
@@ -83,7 +152,7 @@ pub struct InitializeFeeVaultCtx<'info> {
     )]
     pub token_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// CHECK: owner
+    /// CHECK: owner, must sign when allow_permanent_delegate is set
     pub owner: UncheckedAccount<'info>,
 
     #[account(mut)]
@@ -91,6 +160,27 @@ pub struct InitializeFeeVaultCtx<'info> {
 
     pub token_program: Interface<'info, TokenInterface>,
 
+    /// CHECK: protocol config, transfer hook mint is rejected until it is initialized
+    #[account(
+        seeds = [
+            CONFIG_PREFIX,
+        ],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [
+            FEE_VAULT_METADATA_PREFIX,
+            fee_vault.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = 8 + FeeVaultMetadata::INIT_SPACE
+    )]
+    pub fee_vault_metadata: Option<AccountLoader<'info, FeeVaultMetadata>>,
+
     // Sysvar for program account
     pub system_program: Program<'info, System>,
 }
@@ -99,15 +189,23 @@ pub fn handle_initialize_fee_vault(
     ctx: Context<InitializeFeeVaultCtx>,
     params: &InitializeFeeVaultParameters,
 ) -> Result<()> {
+    params.validate_owner_approval(ctx.accounts.owner.is_signer)?;
+
     create_fee_vault(
         &ctx.accounts.token_mint,
+        load_config(&ctx.accounts.config)?.as_ref(),
         params,
         &ctx.accounts.fee_vault,
-        ctx.accounts.owner.key,
-        &ctx.accounts.token_vault.key(),
-        &Pubkey::default(),
-        0,
-        FeeVaultType::NonPdaAccount.into(),
+        ctx.accounts.fee_vault_metadata.as_ref(),
+        &FeeVaultKeys {
+            owner: ctx.accounts.owner.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            token_vault: ctx.accounts.token_vault.key(),
+            token_flag: get_token_program_flags(&ctx.accounts.token_mint).into(),
+            base: Pubkey::default(),
+            fee_vault_bump: 0,
+            fee_vault_type: FeeVaultType::NonPdaAccount.into(),
+        },
     )?;
 
     emit_cpi!(EvtInitializeFeeVault {
@@ -122,29 +220,43 @@ pub fn handle_initialize_fee_vault(
 }
 
 pub fn create_fee_vault<'info>(
-    token_mint: &Box<InterfaceAccount<'info, Mint>>,
+    token_mint: &InterfaceAccount<'info, Mint>,
+    config: Option<&Config>,
     params: &InitializeFeeVaultParameters,
     fee_vault: &AccountLoader<'info, FeeVault>,
-    owner: &Pubkey,
-    token_vault: &Pubkey,
-    base: &Pubkey,
-    fee_vault_bump: u8,
-    fee_vault_type: u8,
+    fee_vault_metadata: Option<&AccountLoader<'info, FeeVaultMetadata>>,
+    keys: &FeeVaultKeys,
 ) -> Result<()> {
-    require!(is_supported_mint(&token_mint)?, FeeVaultError::InvalidMint);
+    let policy = MintExtensionPolicy {
+        config,
+        allow_permanent_delegate: params.allow_permanent_delegate == 1,
+    };
+    require!(
+        is_supported_mint(token_mint, &policy)?,
+        FeeVaultError::InvalidMint
+    );
 
-    params.validate()?;
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    let users = params.validate(current_timestamp)?;
 
+    let fee_vault_key = fee_vault.key();
     let mut fee_vault = fee_vault.load_init()?;
     fee_vault.initialize(
-        owner,
-        get_token_program_flags(&token_mint).into(),
-        &token_mint.key(),
-        token_vault,
-        base,
-        fee_vault_bump,
-        fee_vault_type,
-        &params.users,
+        keys,
+        params.vesting_duration,
+        params.transfer_fee_mode,
+        &users,
     )?;
+
+    match (&params.metadata, fee_vault_metadata) {
+        (Some(metadata), Some(fee_vault_metadata)) => {
+            metadata.validate(users.len())?;
+            fee_vault_metadata
+                .load_init()?
+                .initialize(&fee_vault_key, metadata);
+        }
+        (None, None) => {}
+        _ => return Err(FeeVaultError::InvalidFeeVaultMetadata.into()),
+    }
     Ok(())
 }
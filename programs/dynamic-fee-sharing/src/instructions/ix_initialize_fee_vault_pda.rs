@@ -1,8 +1,11 @@
 use crate::constants::seeds::FEE_VAULT_PREFIX;
 use crate::event::EvtInitializeFeeVault;
-use crate::state::FeeVaultType;
+use crate::state::{load_config, FeeVaultKeys, FeeVaultMetadata, FeeVaultType};
+use crate::utils::token::get_token_program_flags;
 use crate::{
-    constants::seeds::{FEE_VAULT_AUTHORITY_PREFIX, TOKEN_VAULT_PREFIX},
+    constants::seeds::{
+        CONFIG_PREFIX, FEE_VAULT_AUTHORITY_PREFIX, FEE_VAULT_METADATA_PREFIX, TOKEN_VAULT_PREFIX,
+    },
     state::FeeVault,
 };
 use crate::{create_fee_vault, InitializeFeeVaultParameters};
@@ -15,7 +18,7 @@ pub struct InitializeFeeVaultPdaCtx<'info> {
     #[account(
         init,
         seeds = [
-            FEE_VAULT_PREFIX.as_ref(),
+            FEE_VAULT_PREFIX,
             base.key().as_ref(),
             token_mint.key().as_ref(),
         ],
@@ -28,7 +31,7 @@ pub struct InitializeFeeVaultPdaCtx<'info> {
     /// CHECK: pool authority
     #[account(
             seeds = [
-                FEE_VAULT_AUTHORITY_PREFIX.as_ref(),
+                FEE_VAULT_AUTHORITY_PREFIX,
             ],
             bump,
         )]
@@ -37,7 +40,7 @@ pub struct InitializeFeeVaultPdaCtx<'info> {
     #[account(
         init,
         seeds = [
-            TOKEN_VAULT_PREFIX.as_ref(),
+            TOKEN_VAULT_PREFIX,
             fee_vault.key().as_ref(),
         ],
         token::mint = token_mint,
@@ -53,7 +56,7 @@ pub struct InitializeFeeVaultPdaCtx<'info> {
     )]
     pub token_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// CHECK: owner
+    /// CHECK: owner, must sign when allow_permanent_delegate is set
     pub owner: UncheckedAccount<'info>,
 
     pub base: Signer<'info>,
@@ -63,6 +66,27 @@ pub struct InitializeFeeVaultPdaCtx<'info> {
 
     pub token_program: Interface<'info, TokenInterface>,
 
+    /// CHECK: protocol config, transfer hook mint is rejected until it is initialized
+    #[account(
+        seeds = [
+            CONFIG_PREFIX,
+        ],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [
+            FEE_VAULT_METADATA_PREFIX,
+            fee_vault.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = 8 + FeeVaultMetadata::INIT_SPACE
+    )]
+    pub fee_vault_metadata: Option<AccountLoader<'info, FeeVaultMetadata>>,
+
     // Sysvar for program account
     pub system_program: Program<'info, System>,
 }
@@ -71,15 +95,23 @@ pub fn handle_initialize_fee_vault_pda(
     ctx: Context<InitializeFeeVaultPdaCtx>,
     params: &InitializeFeeVaultParameters,
 ) -> Result<()> {
+    params.validate_owner_approval(ctx.accounts.owner.is_signer)?;
+
     create_fee_vault(
         &ctx.accounts.token_mint,
+        load_config(&ctx.accounts.config)?.as_ref(),
         params,
         &ctx.accounts.fee_vault,
-        ctx.accounts.owner.key,
-        &ctx.accounts.token_vault.key(),
-        &ctx.accounts.base.key,
-        ctx.bumps.fee_vault,
-        FeeVaultType::PdaAccount.into(),
+        ctx.accounts.fee_vault_metadata.as_ref(),
+        &FeeVaultKeys {
+            owner: ctx.accounts.owner.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            token_vault: ctx.accounts.token_vault.key(),
+            token_flag: get_token_program_flags(&ctx.accounts.token_mint).into(),
+            base: ctx.accounts.base.key(),
+            fee_vault_bump: ctx.bumps.fee_vault,
+            fee_vault_type: FeeVaultType::PdaAccount.into(),
+        },
     )?;
 
     emit_cpi!(EvtInitializeFeeVault {
@@ -0,0 +1,95 @@
+use crate::event::EvtLockPosition;
+use crate::state::{load_fee_vault, FeeVault};
+use anchor_lang::prelude::*;
+use damm_v2::types::VestingParameters;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct LockPositionCtx<'info> {
+    #[account(
+        constraint = load_fee_vault(&fee_vault)?.owner == owner.key() @ ErrorCode::ConstraintHasOne
+    )]
+    pub fee_vault: AccountLoader<'info, FeeVault>,
+
+    /// CHECK: damm v2 pool, validated by damm v2
+    pub pool: UncheckedAccount<'info>,
+
+    /// CHECK: damm v2 position, validated by damm v2
+    #[account(mut)]
+    pub position: UncheckedAccount<'info>,
+
+    /// CHECK: vesting account, initialized by damm v2
+    #[account(mut)]
+    pub vesting: Signer<'info>,
+
+    /// CHECK: position nft account owned by fee vault, validated by damm v2
+    pub position_nft_account: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: damm v2 event authority
+    pub damm_v2_event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: damm v2 program
+    #[account(address = damm_v2::ID)]
+    pub damm_v2_program: UncheckedAccount<'info>,
+}
+
+impl<'info> LockPositionCtx<'info> {
+    /// Fee vault is the position owner, it signs the cpi with its seeds
+    pub fn damm_v2_accounts(&self) -> damm_v2::cpi::accounts::LockPosition<'info> {
+        damm_v2::cpi::accounts::LockPosition {
+            pool: self.pool.to_account_info(),
+            position: self.position.to_account_info(),
+            vesting: self.vesting.to_account_info(),
+            position_nft_account: self.position_nft_account.to_account_info(),
+            owner: self.fee_vault.to_account_info(),
+            payer: self.payer.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            event_authority: self.damm_v2_event_authority.to_account_info(),
+            program: self.damm_v2_program.to_account_info(),
+        }
+    }
+}
+
+pub fn handle_lock_position(
+    ctx: Context<LockPositionCtx>,
+    params: VestingParameters,
+) -> Result<()> {
+    let fee_vault = ctx.accounts.fee_vault.load()?;
+    fee_vault.validate_position_owner()?;
+    let base = fee_vault.base;
+    let token_mint = fee_vault.token_mint;
+    let fee_vault_bump = fee_vault.fee_vault_bump;
+    let signer_seeds = fee_vault_seeds!(base, token_mint, fee_vault_bump);
+    drop(fee_vault);
+
+    let event = EvtLockPosition {
+        fee_vault: ctx.accounts.fee_vault.key(),
+        position: ctx.accounts.position.key(),
+        vesting: ctx.accounts.vesting.key(),
+        cliff_point: params.cliff_point,
+        period_frequency: params.period_frequency,
+        cliff_unlock_liquidity: params.cliff_unlock_liquidity,
+        liquidity_per_period: params.liquidity_per_period,
+        number_of_period: params.number_of_period,
+    };
+
+    damm_v2::cpi::lock_position(
+        CpiContext::new_with_signer(
+            ctx.accounts.damm_v2_program.to_account_info(),
+            ctx.accounts.damm_v2_accounts(),
+            &[signer_seeds],
+        ),
+        params,
+    )?;
+
+    emit_cpi!(event);
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::error::FeeVaultError;
+use crate::event::EvtMigrateFeeVault;
+use crate::state::{FeeVault, FeeVaultV0};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateFeeVaultCtx<'info> {
+    /// CHECK: fee vault of older version, which can't be loaded in current layout
+    #[account(mut, owner = crate::ID)]
+    pub fee_vault: UncheckedAccount<'info>,
+
+    /// pays rent of the extended account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_migrate_fee_vault(ctx: Context<MigrateFeeVaultCtx>) -> Result<()> {
+    let fee_vault_info = ctx.accounts.fee_vault.to_account_info();
+    let fee_vault = migrate_fee_vault_data(&fee_vault_info.try_borrow_data()?)?;
+
+    let new_len = 8 + FeeVault::INIT_SPACE;
+    let minimum_balance = Rent::get()?.minimum_balance(new_len);
+    let lamports_diff = minimum_balance.saturating_sub(fee_vault_info.lamports());
+    if lamports_diff > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: fee_vault_info.clone(),
+                },
+            ),
+            lamports_diff,
+        )?;
+    }
+
+    fee_vault_info.realloc(new_len, false)?;
+    fee_vault_info.try_borrow_mut_data()?[8..].copy_from_slice(bytemuck::bytes_of(&fee_vault));
+
+    emit_cpi!(EvtMigrateFeeVault {
+        fee_vault: ctx.accounts.fee_vault.key(),
+        version: fee_vault.version,
+    });
+
+    Ok(())
+}
+
+/// Read fee vault account data of an older version in current layout
+pub fn migrate_fee_vault_data(data: &[u8]) -> Result<FeeVault> {
+    require!(
+        data.len() >= 8 && data[..8].eq(FeeVault::DISCRIMINATOR),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    require!(
+        data.len() == 8 + FeeVaultV0::INIT_SPACE,
+        FeeVaultError::InvalidFeeVaultVersion
+    );
+
+    let fee_vault_v0: FeeVaultV0 = bytemuck::pod_read_unaligned(&data[8..]);
+    Ok(fee_vault_v0.migrate())
+}
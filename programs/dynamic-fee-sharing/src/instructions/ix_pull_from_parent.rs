@@ -0,0 +1,157 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::const_pda;
+use crate::constants::seeds::CONFIG_PREFIX;
+use crate::error::FeeVaultError;
+use crate::event::{EvtFundFee, EvtPullFromParent};
+use crate::instructions::claim_user_fee;
+use crate::math::SafeMath;
+use crate::state::{load_config, load_fee_vault, require_not_paused, FeeVault, PauseFlag};
+use crate::utils::event::{emit_event_cpi, emit_expired_shares};
+use crate::utils::token::{
+    calculate_transfer_fee_excluded_amount, require_whitelisted_transfer_hook, TokenVaultAccounts,
+};
+
+#[derive(Accounts)]
+pub struct PullFromParentCtx<'info> {
+    #[account(
+        mut,
+        constraint = load_fee_vault(&parent_fee_vault)?.token_mint == token_mint.key() @ ErrorCode::ConstraintHasOne,
+        constraint = parent_fee_vault.key() != child_fee_vault.key() @ FeeVaultError::InvalidFeeVault
+    )]
+    pub parent_fee_vault: AccountLoader<'info, FeeVault>,
+
+    #[account(
+        mut,
+        address = parent_fee_vault.load()?.token_vault
+    )]
+    pub parent_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// child fee vault, registered as a share holder of parent fee vault
+    #[account(
+        mut,
+        constraint = load_fee_vault(&child_fee_vault)?.token_mint == token_mint.key() @ ErrorCode::ConstraintHasOne
+    )]
+    pub child_fee_vault: AccountLoader<'info, FeeVault>,
+
+    #[account(
+        mut,
+        address = child_fee_vault.load()?.token_vault
+    )]
+    pub child_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: fee vault authority
+    #[account(
+        address = const_pda::fee_vault_authority::ID
+    )]
+    pub fee_vault_authority: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: protocol config, pause flags are not applied until it is initialized
+    #[account(
+        seeds = [
+            CONFIG_PREFIX,
+        ],
+        bump,
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: event authority of self cpi, address is precomputed instead of deriving the bump
+    #[account(address = const_pda::event_authority::ID)]
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: self cpi fails if it is not this program
+    #[account(address = crate::ID)]
+    pub program: UncheckedAccount<'info>,
+}
+
+/// Claim fee accrued by child fee vault in parent fee vault, and fund it into child fee vault.
+/// Protocol fee was charged when parent fee vault was funded, so it is not charged again.
+pub fn handle_pull_from_parent<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, PullFromParentCtx<'info>>,
+    index: u8,
+) -> Result<()> {
+    let config = load_config(&ctx.accounts.config)?;
+    require_not_paused(config.as_ref(), PauseFlag::Fund)?;
+    require_whitelisted_transfer_hook(&ctx.accounts.token_mint, config.as_ref())?;
+
+    let Some(claim_event) = claim_user_fee(
+        &ctx.accounts.parent_fee_vault,
+        &ctx.accounts.config,
+        &TokenVaultAccounts {
+            fee_vault_authority: ctx.accounts.fee_vault_authority.to_account_info(),
+            token_mint: &ctx.accounts.token_mint,
+            token_vault: &ctx.accounts.parent_token_vault,
+            token_program: &ctx.accounts.token_program,
+            remaining_accounts: ctx.remaining_accounts,
+        },
+        &ctx.accounts.child_token_vault,
+        &ctx.accounts.event_authority,
+        &ctx.accounts.child_fee_vault.key(),
+        index,
+    )?
+    else {
+        return Ok(());
+    };
+
+    let received_amount = claim_event.net_amount;
+    let transfer_fee = claim_event.transfer_fee;
+    emit_event_cpi(&ctx.accounts.event_authority, &claim_event)?;
+
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    let mut child_fee_vault = ctx.accounts.child_fee_vault.load_mut()?;
+    let reserved_transfer_fee = if child_fee_vault.is_transfer_fee_carried() {
+        calculate_transfer_fee_excluded_amount(&ctx.accounts.token_mint, received_amount)?
+            .transfer_fee
+    } else {
+        0
+    };
+    let funded_amount = received_amount.safe_sub(reserved_transfer_fee)?;
+    let expired_flags = child_fee_vault.expired_flags();
+    child_fee_vault.fund_fee(funded_amount, current_timestamp)?;
+    child_fee_vault.reserve_transfer_fee(reserved_transfer_fee)?;
+
+    emit_expired_shares(
+        &ctx.accounts.event_authority,
+        ctx.accounts.child_fee_vault.key(),
+        &child_fee_vault,
+        expired_flags,
+    )?;
+
+    emit_event_cpi(
+        &ctx.accounts.event_authority,
+        &EvtFundFee {
+            source_program: Pubkey::default(),
+            fee_vault: ctx.accounts.child_fee_vault.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            payload: vec![],
+            funded_amount,
+            protocol_fee: 0,
+            transfer_fee,
+            reserved_transfer_fee,
+            total_funded_fee: child_fee_vault.total_funded_fee,
+            fee_per_share: child_fee_vault.fee_per_share,
+            locked_fee: child_fee_vault.locked_fee,
+            transfer_fee_reserve: child_fee_vault.transfer_fee_reserve,
+            total_share: child_fee_vault.total_share,
+            vesting_end_timestamp: child_fee_vault.vesting_end_timestamp,
+            last_updated_timestamp: child_fee_vault.last_updated_timestamp,
+        },
+    )?;
+
+    emit_event_cpi(
+        &ctx.accounts.event_authority,
+        &EvtPullFromParent {
+            parent_fee_vault: ctx.accounts.parent_fee_vault.key(),
+            child_fee_vault: ctx.accounts.child_fee_vault.key(),
+            index,
+            amount: received_amount,
+        },
+    )?;
+
+    Ok(())
+}
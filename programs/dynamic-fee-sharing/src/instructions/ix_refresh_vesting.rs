@@ -0,0 +1,58 @@
+use crate::event::EvtRefreshVesting;
+use crate::state::{load_fee_vault, FeeVault};
+use anchor_lang::prelude::*;
+
+// permissionless as in damm v2, refreshing only releases vested liquidity into the position
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RefreshVestingCtx<'info> {
+    pub fee_vault: AccountLoader<'info, FeeVault>,
+
+    /// CHECK: damm v2 pool, validated by damm v2
+    pub pool: UncheckedAccount<'info>,
+
+    /// CHECK: damm v2 position, validated by damm v2
+    #[account(mut)]
+    pub position: UncheckedAccount<'info>,
+
+    /// CHECK: position nft account owned by fee vault, validated by damm v2
+    pub position_nft_account: UncheckedAccount<'info>,
+
+    /// CHECK: damm v2 program
+    #[account(address = damm_v2::ID)]
+    pub damm_v2_program: UncheckedAccount<'info>,
+}
+
+impl<'info> RefreshVestingCtx<'info> {
+    /// Fee vault is the position owner, damm v2 does not require it to sign
+    pub fn damm_v2_accounts(&self) -> damm_v2::cpi::accounts::RefreshVesting<'info> {
+        damm_v2::cpi::accounts::RefreshVesting {
+            pool: self.pool.to_account_info(),
+            position: self.position.to_account_info(),
+            position_nft_account: self.position_nft_account.to_account_info(),
+            owner: self.fee_vault.to_account_info(),
+        }
+    }
+}
+
+/// Vesting accounts of the position are passed in remaining accounts
+pub fn handle_refresh_vesting<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, RefreshVestingCtx<'info>>,
+) -> Result<()> {
+    load_fee_vault(&ctx.accounts.fee_vault)?.validate_position_owner()?;
+
+    damm_v2::cpi::refresh_vesting(
+        CpiContext::new(
+            ctx.accounts.damm_v2_program.to_account_info(),
+            ctx.accounts.damm_v2_accounts(),
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+    )?;
+
+    emit_cpi!(EvtRefreshVesting {
+        fee_vault: ctx.accounts.fee_vault.key(),
+        position: ctx.accounts.position.key(),
+    });
+
+    Ok(())
+}
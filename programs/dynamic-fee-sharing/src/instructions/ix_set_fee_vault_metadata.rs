@@ -0,0 +1,109 @@
+use crate::constants::seeds::FEE_VAULT_METADATA_PREFIX;
+use crate::constants::{
+    MAX_METADATA_LABEL_LEN, MAX_METADATA_NAME_LEN, MAX_METADATA_URI_LEN, MAX_USER,
+};
+use crate::error::FeeVaultError;
+use crate::event::EvtSetFeeVaultMetadata;
+use crate::state::{load_fee_vault, FeeVault, FeeVaultMetadata};
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Default, PartialEq)]
+pub struct FeeVaultMetadataParameters {
+    pub name: String,
+    pub uri: String,
+    pub labels: Vec<String>, // indexed by share holder slot in fee vault
+}
+
+impl FeeVaultMetadataParameters {
+    pub fn validate(&self, number_of_user: usize) -> Result<()> {
+        require!(
+            self.name.len() <= MAX_METADATA_NAME_LEN
+                && self.uri.len() <= MAX_METADATA_URI_LEN
+                && self.labels.len() <= number_of_user.min(MAX_USER),
+            FeeVaultError::InvalidFeeVaultMetadata
+        );
+        for label in self.labels.iter() {
+            require!(
+                label.len() <= MAX_METADATA_LABEL_LEN,
+                FeeVaultError::InvalidFeeVaultMetadata
+            );
+        }
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeFeeVaultMetadataCtx<'info> {
+    #[account(
+        constraint = load_fee_vault(&fee_vault)?.owner == owner.key() @ ErrorCode::ConstraintHasOne
+    )]
+    pub fee_vault: AccountLoader<'info, FeeVault>,
+
+    #[account(
+        init,
+        seeds = [
+            FEE_VAULT_METADATA_PREFIX,
+            fee_vault.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = 8 + FeeVaultMetadata::INIT_SPACE
+    )]
+    pub fee_vault_metadata: AccountLoader<'info, FeeVaultMetadata>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateFeeVaultMetadataCtx<'info> {
+    #[account(
+        constraint = load_fee_vault(&fee_vault)?.owner == owner.key() @ ErrorCode::ConstraintHasOne
+    )]
+    pub fee_vault: AccountLoader<'info, FeeVault>,
+
+    #[account(mut, has_one = fee_vault)]
+    pub fee_vault_metadata: AccountLoader<'info, FeeVaultMetadata>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn handle_initialize_fee_vault_metadata(
+    ctx: Context<InitializeFeeVaultMetadataCtx>,
+    params: FeeVaultMetadataParameters,
+) -> Result<()> {
+    params.validate(ctx.accounts.fee_vault.load()?.number_of_user())?;
+
+    let mut fee_vault_metadata = ctx.accounts.fee_vault_metadata.load_init()?;
+    fee_vault_metadata.initialize(&ctx.accounts.fee_vault.key(), &params);
+
+    emit_cpi!(EvtSetFeeVaultMetadata {
+        fee_vault: ctx.accounts.fee_vault.key(),
+        metadata: params,
+    });
+
+    Ok(())
+}
+
+pub fn handle_update_fee_vault_metadata(
+    ctx: Context<UpdateFeeVaultMetadataCtx>,
+    params: FeeVaultMetadataParameters,
+) -> Result<()> {
+    params.validate(ctx.accounts.fee_vault.load()?.number_of_user())?;
+
+    let mut fee_vault_metadata = ctx.accounts.fee_vault_metadata.load_mut()?;
+    fee_vault_metadata.update(&params);
+
+    emit_cpi!(EvtSetFeeVaultMetadata {
+        fee_vault: ctx.accounts.fee_vault.key(),
+        metadata: params,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use crate::event::EvtSetPauseFlags;
+use crate::state::Config;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPauseFlagsCtx<'info> {
+    #[account(mut, has_one = admin)]
+    pub config: AccountLoader<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handle_set_pause_flags(ctx: Context<SetPauseFlagsCtx>, pause_flags: u8) -> Result<()> {
+    let mut config = ctx.accounts.config.load_mut()?;
+    config.set_pause_flags(pause_flags)?;
+
+    emit_cpi!(EvtSetPauseFlags {
+        config: ctx.accounts.config.key(),
+        pause_flags,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use crate::event::EvtSetTransferHookPrograms;
+use crate::state::Config;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetTransferHookProgramsCtx<'info> {
+    #[account(mut, has_one = admin)]
+    pub config: AccountLoader<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handle_set_transfer_hook_programs(
+    ctx: Context<SetTransferHookProgramsCtx>,
+    transfer_hook_programs: Vec<Pubkey>,
+) -> Result<()> {
+    let mut config = ctx.accounts.config.load_mut()?;
+    config.set_transfer_hook_programs(&transfer_hook_programs)?;
+
+    emit_cpi!(EvtSetTransferHookPrograms {
+        config: ctx.accounts.config.key(),
+        transfer_hook_programs,
+    });
+
+    Ok(())
+}
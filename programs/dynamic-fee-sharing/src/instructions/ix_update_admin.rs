@@ -0,0 +1,28 @@
+use crate::event::EvtUpdateAdmin;
+use crate::state::Config;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateAdminCtx<'info> {
+    #[account(mut, has_one = admin)]
+    pub config: AccountLoader<'info, Config>,
+
+    pub admin: Signer<'info>,
+
+    // new admin signs to accept, so admin cannot be handed over to an unusable address
+    pub new_admin: Signer<'info>,
+}
+
+pub fn handle_update_admin(ctx: Context<UpdateAdminCtx>) -> Result<()> {
+    let mut config = ctx.accounts.config.load_mut()?;
+    config.admin = ctx.accounts.new_admin.key();
+
+    emit_cpi!(EvtUpdateAdmin {
+        config: ctx.accounts.config.key(),
+        old_admin: ctx.accounts.admin.key(),
+        new_admin: ctx.accounts.new_admin.key(),
+    });
+
+    Ok(())
+}
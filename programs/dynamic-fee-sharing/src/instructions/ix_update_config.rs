@@ -0,0 +1,31 @@
+use crate::event::EvtUpdateConfig;
+use crate::state::Config;
+use crate::ConfigParameters;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateConfigCtx<'info> {
+    #[account(mut, has_one = admin)]
+    pub config: AccountLoader<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handle_update_config(
+    ctx: Context<UpdateConfigCtx>,
+    params: &ConfigParameters,
+) -> Result<()> {
+    params.validate()?;
+
+    let mut config = ctx.accounts.config.load_mut()?;
+    config.update(&params.treasury, params.protocol_fee_bps);
+
+    emit_cpi!(EvtUpdateConfig {
+        config: ctx.accounts.config.key(),
+        treasury: params.treasury,
+        protocol_fee_bps: params.protocol_fee_bps,
+    });
+
+    Ok(())
+}
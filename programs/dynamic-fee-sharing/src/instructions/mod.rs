@@ -8,3 +8,31 @@ pub mod ix_initialize_fee_vault_pda;
 pub use ix_initialize_fee_vault_pda::*;
 pub mod ix_fund_by_claiming_fee;
 pub use ix_fund_by_claiming_fee::*;
+pub mod ix_permanent_lock_position;
+pub use ix_permanent_lock_position::*;
+pub mod ix_lock_position;
+pub use ix_lock_position::*;
+pub mod ix_refresh_vesting;
+pub use ix_refresh_vesting::*;
+pub mod ix_initialize_config;
+pub use ix_initialize_config::*;
+pub mod ix_update_config;
+pub use ix_update_config::*;
+pub mod ix_update_admin;
+pub use ix_update_admin::*;
+pub mod ix_set_pause_flags;
+pub use ix_set_pause_flags::*;
+pub mod ix_freeze_vault;
+pub use ix_freeze_vault::*;
+pub mod ix_set_transfer_hook_programs;
+pub use ix_set_transfer_hook_programs::*;
+pub mod ix_fund_fee_with_sol;
+pub use ix_fund_fee_with_sol::*;
+pub mod ix_claim_fee_and_unwrap;
+pub use ix_claim_fee_and_unwrap::*;
+pub mod ix_migrate_fee_vault;
+pub use ix_migrate_fee_vault::*;
+pub mod ix_pull_from_parent;
+pub use ix_pull_from_parent::*;
+pub mod ix_set_fee_vault_metadata;
+pub use ix_set_fee_vault_metadata::*;
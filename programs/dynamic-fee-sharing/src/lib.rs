@@ -1,4 +1,8 @@
 #![allow(unexpected_cfgs)]
+#![cfg_attr(
+    test,
+    allow(clippy::bool_assert_comparison, clippy::legacy_numeric_constants)
+)]
 
 use anchor_lang::prelude::*;
 #[macro_use]
@@ -33,18 +37,136 @@ pub mod dynamic_fee_sharing {
         instructions::handle_initialize_fee_vault_pda(ctx, &params)
     }
 
-    pub fn fund_fee(ctx: Context<FundFeeCtx>, max_amount: u64) -> Result<()> {
+    pub fn fund_fee<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, FundFeeCtx<'info>>,
+        max_amount: u64,
+    ) -> Result<()> {
         instructions::handle_fund_fee(ctx, max_amount)
     }
 
-    pub fn fund_by_claiming_fee(
-        ctx: Context<FundByClaimingFeeCtx>,
+    pub fn fund_by_claiming_fee<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, FundByClaimingFeeCtx<'info>>,
         payload: Vec<u8>,
     ) -> Result<()> {
-        instructions::handle_fund_by_claiming_fee(ctx, payload)
+        instructions::handle_fund_by_claiming_fee(ctx, payload, None, None)
     }
 
-    pub fn claim_fee(ctx: Context<ClaimFeeCtx>, index: u8) -> Result<()> {
+    pub fn fund_by_claiming_fee_with_minimum<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, FundByClaimingFeeCtx<'info>>,
+        payload: Vec<u8>,
+        min_claimed_amount: u64,
+    ) -> Result<()> {
+        instructions::handle_fund_by_claiming_fee(ctx, payload, None, Some(min_claimed_amount))
+    }
+
+    pub fn fund_by_claiming_fee_and_swap<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, FundByClaimingFeeCtx<'info>>,
+        payload: Vec<u8>,
+        minimum_amount_out: u64,
+        min_claimed_amount: u64,
+    ) -> Result<()> {
+        instructions::handle_fund_by_claiming_fee(
+            ctx,
+            payload,
+            Some(minimum_amount_out),
+            Some(min_claimed_amount),
+        )
+    }
+
+    pub fn claim_fee<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, ClaimFeeCtx<'info>>,
+        index: u8,
+    ) -> Result<()> {
         instructions::handle_claim_fee(ctx, index)
     }
+
+    pub fn fund_fee_with_sol(ctx: Context<FundFeeWithSolCtx>, amount: u64) -> Result<()> {
+        instructions::handle_fund_fee_with_sol(ctx, amount)
+    }
+
+    pub fn claim_fee_and_unwrap<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, ClaimFeeAndUnwrapCtx<'info>>,
+        index: u8,
+    ) -> Result<()> {
+        instructions::handle_claim_fee_and_unwrap(ctx, index)
+    }
+
+    pub fn pull_from_parent<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, PullFromParentCtx<'info>>,
+        index: u8,
+    ) -> Result<()> {
+        instructions::handle_pull_from_parent(ctx, index)
+    }
+
+    pub fn initialize_config(
+        ctx: Context<InitializeConfigCtx>,
+        params: ConfigParameters,
+    ) -> Result<()> {
+        instructions::handle_initialize_config(ctx, &params)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfigCtx>, params: ConfigParameters) -> Result<()> {
+        instructions::handle_update_config(ctx, &params)
+    }
+
+    pub fn update_admin(ctx: Context<UpdateAdminCtx>) -> Result<()> {
+        instructions::handle_update_admin(ctx)
+    }
+
+    pub fn set_pause_flags(ctx: Context<SetPauseFlagsCtx>, pause_flags: u8) -> Result<()> {
+        instructions::handle_set_pause_flags(ctx, pause_flags)
+    }
+
+    pub fn set_transfer_hook_programs(
+        ctx: Context<SetTransferHookProgramsCtx>,
+        transfer_hook_programs: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::handle_set_transfer_hook_programs(ctx, transfer_hook_programs)
+    }
+
+    pub fn freeze_vault(ctx: Context<FreezeVaultCtx>, freeze_funding: bool) -> Result<()> {
+        instructions::handle_freeze_vault(ctx, freeze_funding)
+    }
+
+    pub fn unfreeze_vault(ctx: Context<FreezeVaultCtx>) -> Result<()> {
+        instructions::handle_unfreeze_vault(ctx)
+    }
+
+    pub fn initialize_fee_vault_metadata(
+        ctx: Context<InitializeFeeVaultMetadataCtx>,
+        params: FeeVaultMetadataParameters,
+    ) -> Result<()> {
+        instructions::handle_initialize_fee_vault_metadata(ctx, params)
+    }
+
+    pub fn update_fee_vault_metadata(
+        ctx: Context<UpdateFeeVaultMetadataCtx>,
+        params: FeeVaultMetadataParameters,
+    ) -> Result<()> {
+        instructions::handle_update_fee_vault_metadata(ctx, params)
+    }
+
+    pub fn migrate_fee_vault(ctx: Context<MigrateFeeVaultCtx>) -> Result<()> {
+        instructions::handle_migrate_fee_vault(ctx)
+    }
+
+    pub fn permanent_lock_position(
+        ctx: Context<PermanentLockPositionCtx>,
+        permanent_lock_liquidity: u128,
+    ) -> Result<()> {
+        instructions::handle_permanent_lock_position(ctx, permanent_lock_liquidity)
+    }
+
+    pub fn lock_position(
+        ctx: Context<LockPositionCtx>,
+        params: damm_v2::types::VestingParameters,
+    ) -> Result<()> {
+        instructions::handle_lock_position(ctx, params)
+    }
+
+    pub fn refresh_vesting<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, RefreshVestingCtx<'info>>,
+    ) -> Result<()> {
+        instructions::handle_refresh_vesting(ctx)
+    }
 }
@@ -7,6 +7,15 @@ macro_rules! fee_vault_authority_seeds {
     };
 }
 
+macro_rules! event_authority_seeds {
+    () => {
+        &[
+            crate::constants::seeds::EVENT_AUTHORITY_PREFIX,
+            &[crate::const_pda::event_authority::BUMP],
+        ]
+    };
+}
+
 macro_rules! fee_vault_seeds {
     ($base:expr, $token_mint:expr, $bump:expr) => {
         &[
@@ -0,0 +1,110 @@
+use crate::{
+    constants::{MAX_BASIS_POINT, MAX_TRANSFER_HOOK_PROGRAM},
+    error::FeeVaultError,
+    math::SafeMath,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use static_assertions::const_assert_eq;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PauseFlag {
+    Fund = 1 << 0,
+    Claim = 1 << 1,
+    FundByClaiming = 1 << 2,
+}
+
+pub const ALL_PAUSE_FLAGS: u8 =
+    PauseFlag::Fund as u8 | PauseFlag::Claim as u8 | PauseFlag::FundByClaiming as u8;
+
+#[account(zero_copy)]
+#[derive(InitSpace, Debug, Default)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub treasury: Pubkey, // owner of token accounts receiving protocol fee
+    pub protocol_fee_bps: u16,
+    pub pause_flags: u8, // bitmask of PauseFlag
+    pub padding_0: [u8; 5],
+    pub transfer_hook_programs: [Pubkey; MAX_TRANSFER_HOOK_PROGRAM], // default pubkey is empty slot
+    pub padding: [u64; 8],
+}
+const_assert_eq!(Config::INIT_SPACE, 264);
+
+impl Config {
+    pub fn initialize(&mut self, admin: &Pubkey, treasury: &Pubkey, protocol_fee_bps: u16) {
+        self.admin = *admin;
+        self.update(treasury, protocol_fee_bps);
+    }
+
+    pub fn update(&mut self, treasury: &Pubkey, protocol_fee_bps: u16) {
+        self.treasury = *treasury;
+        self.protocol_fee_bps = protocol_fee_bps;
+    }
+
+    pub fn set_pause_flags(&mut self, pause_flags: u8) -> Result<()> {
+        require!(
+            pause_flags & !ALL_PAUSE_FLAGS == 0,
+            FeeVaultError::InvalidPauseFlags
+        );
+        self.pause_flags = pause_flags;
+        Ok(())
+    }
+
+    pub fn is_paused(&self, flag: PauseFlag) -> bool {
+        self.pause_flags & flag as u8 != 0
+    }
+
+    pub fn set_transfer_hook_programs(&mut self, transfer_hook_programs: &[Pubkey]) -> Result<()> {
+        require!(
+            transfer_hook_programs.len() <= MAX_TRANSFER_HOOK_PROGRAM,
+            FeeVaultError::InvalidTransferHookPrograms
+        );
+        for (i, program) in transfer_hook_programs.iter().enumerate() {
+            require!(
+                program.ne(&Pubkey::default()) && !transfer_hook_programs[..i].contains(program),
+                FeeVaultError::InvalidTransferHookPrograms
+            );
+        }
+
+        self.transfer_hook_programs = [Pubkey::default(); MAX_TRANSFER_HOOK_PROGRAM];
+        self.transfer_hook_programs[..transfer_hook_programs.len()]
+            .copy_from_slice(transfer_hook_programs);
+        Ok(())
+    }
+
+    pub fn is_whitelisted_transfer_hook_program(&self, program: &Pubkey) -> bool {
+        program.ne(&Pubkey::default()) && self.transfer_hook_programs.contains(program)
+    }
+
+    /// Protocol fee is rounded down in favor of share holders
+    pub fn get_protocol_fee(&self, amount: u64) -> Result<u64> {
+        let protocol_fee = u128::from(amount)
+            .safe_mul(self.protocol_fee_bps.into())?
+            .safe_div(MAX_BASIS_POINT.into())?;
+        Ok(u64::try_from(protocol_fee).map_err(|_| FeeVaultError::MathOverflow)?)
+    }
+}
+
+/// Load config if it is initialized, config is optional until admin initializes it
+pub fn load_config(config: &AccountInfo) -> Result<Option<Config>> {
+    if config.owner.ne(&crate::ID) {
+        return Ok(None);
+    }
+
+    let config_data = config.try_borrow_data()?;
+    require!(
+        config_data.len() == 8 + Config::INIT_SPACE && config_data[..8].eq(Config::DISCRIMINATOR),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    Ok(Some(*bytemuck::from_bytes::<Config>(&config_data[8..])))
+}
+
+/// Nothing is paused while config is uninitialized, since pause flags are only set by admin after
+/// initializing it
+pub fn require_not_paused(config: Option<&Config>, flag: PauseFlag) -> Result<()> {
+    if let Some(config) = config {
+        require!(!config.is_paused(flag), FeeVaultError::Paused);
+    }
+    Ok(())
+}
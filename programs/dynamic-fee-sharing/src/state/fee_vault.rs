@@ -1,10 +1,11 @@
 use crate::{
-    constants::{MAX_USER, PRECISION_SCALE},
+    constants::{FEE_VAULT_VERSION, MAX_USER, PRECISION_SCALE},
     error::FeeVaultError,
     instructions::UserShare,
     math::{mul_shr, shl_div, SafeMath},
 };
 use anchor_lang::prelude::*;
+use core::cell::Ref;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use static_assertions::const_assert_eq;
 
@@ -24,6 +25,22 @@ pub enum FeeVaultType {
     PdaAccount,
 }
 
+#[repr(u8)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    IntoPrimitive,
+    TryFromPrimitive,
+    AnchorDeserialize,
+    AnchorSerialize,
+)]
+pub enum TransferFeeMode {
+    Deducted, // share holders receive claimed fee net of token 2022 transfer fee
+    Carried,  // transfer fee on claim is paid from transfer_fee_reserve set aside on funding
+}
+
 #[account(zero_copy)]
 #[derive(InitSpace, Debug, Default)]
 pub struct FeeVault {
@@ -33,96 +50,318 @@ pub struct FeeVault {
     pub token_flag: u8, // indicate whether token is spl-token or token2022
     pub fee_vault_type: u8,
     pub fee_vault_bump: u8,
-    pub padding_0: [u8; 13],
+    pub is_frozen: u8,         // owner froze the vault, claiming is rejected
+    pub is_funding_frozen: u8, // funding is also rejected while vault is frozen
+    pub transfer_fee_mode: u8,
+    pub version: u8, // layout version, older vaults are brought to current layout by migrate_fee_vault
+    pub padding_0: [u8; 9],
     pub total_share: u32,
     pub padding_1: [u8; 4],
     pub total_funded_fee: u64,
     pub fee_per_share: u128,
     pub base: Pubkey,
+    pub vesting_duration: u64, // 0 means funded fee is distributed immediately
+    pub vesting_end_timestamp: u64,
+    pub last_updated_timestamp: u64, // last time vested fee was released
+    pub locked_fee: u64,             // funded fee which is not released to fee_per_share yet
+    pub transfer_fee_reserve: u64,   // set aside to carry transfer fee on claim
+    pub padding_2: [u8; 8],
     pub padding: [u128; 4],
     pub users: [UserFee; MAX_USER],
 }
-const_assert_eq!(FeeVault::INIT_SPACE, 640);
+const_assert_eq!(FeeVault::INIT_SPACE, 768);
 
 #[zero_copy]
 #[derive(InitSpace, Debug, Default)]
 pub struct UserFee {
     pub address: Pubkey,
     pub share: u32,
-    pub padding_0: [u8; 4],
+    pub is_expired: u8, // indicate whether share has been excluded from total_share
+    pub padding_0: [u8; 3],
     pub fee_claimed: u64,
-    pub padding: [u8; 16], // padding for future use
+    pub end_timestamp: u64, // 0 means share never expires
+    pub pending_fee: u64,   // fee accrued before expiry but not claimed yet
     pub fee_per_share_checkpoint: u128,
+    pub padding: [u64; 2],
+}
+const_assert_eq!(UserFee::INIT_SPACE, 96);
+
+/// Addresses and pda of a fee vault, set at initialization. Base is default and bump is 0 for
+/// fee vault that is not a pda
+#[derive(Debug, Default)]
+pub struct FeeVaultKeys {
+    pub owner: Pubkey,
+    pub token_mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub token_flag: u8,
+    pub base: Pubkey,
+    pub fee_vault_bump: u8,
+    pub fee_vault_type: u8,
 }
-const_assert_eq!(UserFee::INIT_SPACE, 80);
 
 impl FeeVault {
     pub fn initialize(
         &mut self,
-        owner: &Pubkey,
-        token_flag: u8,
-        token_mint: &Pubkey,
-        token_vault: &Pubkey,
-        base: &Pubkey,
-        fee_vault_bump: u8,
-        fee_vault_type: u8,
+        keys: &FeeVaultKeys,
+        vesting_duration: u64,
+        transfer_fee_mode: u8,
         users: &[UserShare],
     ) -> Result<()> {
-        self.owner = *owner;
-        self.token_flag = token_flag;
-        self.token_mint = *token_mint;
-        self.token_vault = *token_vault;
+        self.owner = keys.owner;
+        self.token_flag = keys.token_flag;
+        self.token_mint = keys.token_mint;
+        self.token_vault = keys.token_vault;
         let mut total_share = 0;
-        for i in 0..users.len() {
-            self.users[i] = UserFee {
-                address: users[i].address,
-                share: users[i].share,
+        for (user_fee, user) in self.users.iter_mut().zip(users) {
+            *user_fee = UserFee {
+                address: user.address,
+                share: user.share,
+                end_timestamp: user.end_timestamp,
                 ..Default::default()
             };
-            total_share = total_share.safe_add(users[i].share)?;
+            total_share = total_share.safe_add(user.share)?;
         }
         self.total_share = total_share;
-        self.base = *base;
-        self.fee_vault_bump = fee_vault_bump;
-        self.fee_vault_type = fee_vault_type;
+        self.base = keys.base;
+        self.fee_vault_bump = keys.fee_vault_bump;
+        self.fee_vault_type = keys.fee_vault_type;
+        self.vesting_duration = vesting_duration;
+        self.transfer_fee_mode = transfer_fee_mode;
+        self.version = FEE_VAULT_VERSION;
 
         Ok(())
     }
 
-    pub fn fund_fee(&mut self, amount: u64) -> Result<()> {
-        self.total_funded_fee = self.total_funded_fee.safe_add(amount)?;
+    /// Bring the vault up to current_timestamp: release vested fee and exclude expired shares.
+    /// Expired shares are settled in order of end_timestamp, so fee vested before expiry still
+    /// accrues to them. Shares expiring last also receive the fee still locked at their expiry,
+    /// since no share is left to vest it to and funding is rejected afterwards
+    pub fn update(&mut self, current_timestamp: u64) -> Result<()> {
+        while let Some(index) = self.next_expired_share(current_timestamp) {
+            let end_timestamp = self.users[index].end_timestamp;
+            self.release_vested_fee(end_timestamp)?;
+            if !self.has_share_active_after(end_timestamp) {
+                self.release_locked_fee()?;
+            }
+            self.expire_share(index)?;
+        }
+
+        self.release_vested_fee(current_timestamp)
+    }
+
+    /// is_expired flag of every share, taken before an update to find the shares it expires
+    pub fn expired_flags(&self) -> [u8; MAX_USER] {
+        self.users.map(|user| user.is_expired)
+    }
+
+    fn next_expired_share(&self, current_timestamp: u64) -> Option<usize> {
+        self.users
+            .iter()
+            .enumerate()
+            .filter(|(_, user)| {
+                user.is_expired == 0
+                    && user.end_timestamp != 0
+                    && user.end_timestamp <= current_timestamp
+            })
+            .min_by_key(|(_, user)| user.end_timestamp)
+            .map(|(index, _)| index)
+    }
+
+    fn has_share_active_after(&self, timestamp: u64) -> bool {
+        self.users.iter().any(|user| {
+            user.share > 0
+                && user.is_expired == 0
+                && (user.end_timestamp == 0 || user.end_timestamp > timestamp)
+        })
+    }
+
+    /// Exclude share from total_share, the fee accrued up to this point is kept in pending_fee
+    /// so user still can claim it later
+    fn expire_share(&mut self, index: usize) -> Result<()> {
+        let user = &mut self.users[index];
+        let fee_per_share_delta = self.fee_per_share.safe_sub(user.fee_per_share_checkpoint)?;
+        let pending_fee: u64 = mul_shr(user.share.into(), fee_per_share_delta, PRECISION_SCALE)
+            .ok_or(FeeVaultError::MathOverflow)?
+            .try_into()
+            .map_err(|_| FeeVaultError::MathOverflow)?;
+
+        user.pending_fee = user.pending_fee.safe_add(pending_fee)?;
+        user.fee_per_share_checkpoint = self.fee_per_share;
+        user.is_expired = 1;
+        self.total_share = self.total_share.safe_sub(user.share)?;
+
+        Ok(())
+    }
+
+    /// Linearly release locked fee into fee_per_share until vesting_end_timestamp
+    fn release_vested_fee(&mut self, current_timestamp: u64) -> Result<()> {
+        if current_timestamp <= self.last_updated_timestamp {
+            return Ok(());
+        }
+
+        // keep fee locked if there is no share to distribute to
+        if self.locked_fee > 0 && self.total_share > 0 {
+            let released_fee = if current_timestamp >= self.vesting_end_timestamp {
+                self.locked_fee
+            } else {
+                let elapsed = current_timestamp.safe_sub(self.last_updated_timestamp)?;
+                let remaining = self
+                    .vesting_end_timestamp
+                    .safe_sub(self.last_updated_timestamp)?;
+                u128::from(self.locked_fee)
+                    .safe_mul(elapsed.into())?
+                    .safe_div(remaining.into())?
+                    .try_into()
+                    .map_err(|_| FeeVaultError::MathOverflow)?
+            };
+
+            self.locked_fee = self.locked_fee.safe_sub(released_fee)?;
+            self.distribute_fee(released_fee)?;
+        }
 
+        self.last_updated_timestamp = current_timestamp;
+
+        Ok(())
+    }
+
+    fn release_locked_fee(&mut self) -> Result<()> {
+        if self.locked_fee > 0 {
+            let locked_fee = self.locked_fee;
+            self.locked_fee = 0;
+            self.distribute_fee(locked_fee)?;
+        }
+
+        Ok(())
+    }
+
+    fn distribute_fee(&mut self, amount: u64) -> Result<()> {
         let fee_per_share = shl_div(amount, self.total_share.into(), PRECISION_SCALE)
-            .ok_or_else(|| FeeVaultError::MathOverflow)?;
+            .ok_or(FeeVaultError::MathOverflow)?;
 
         self.fee_per_share = self.fee_per_share.safe_add(fee_per_share)?;
 
         Ok(())
     }
 
-    pub fn validate_and_claim_fee(&mut self, index: u8, signer: &Pubkey) -> Result<u64> {
+    pub fn freeze(&mut self, freeze_funding: bool) {
+        self.is_frozen = 1;
+        self.is_funding_frozen = freeze_funding.into();
+    }
+
+    pub fn unfreeze(&mut self) {
+        self.is_frozen = 0;
+        self.is_funding_frozen = 0;
+    }
+
+    pub fn fund_fee(&mut self, amount: u64, current_timestamp: u64) -> Result<()> {
+        require!(self.is_funding_frozen == 0, FeeVaultError::VaultFrozen);
+        self.update(current_timestamp)?;
+        require!(self.total_share > 0, FeeVaultError::NoActiveShare);
+
+        self.total_funded_fee = self.total_funded_fee.safe_add(amount)?;
+
+        if self.vesting_duration == 0 {
+            return self.distribute_fee(amount);
+        }
+
+        // restart the vesting window with leftover and newly funded fee
+        self.locked_fee = self.locked_fee.safe_add(amount)?;
+        self.vesting_end_timestamp = current_timestamp.safe_add(self.vesting_duration)?;
+
+        Ok(())
+    }
+
+    pub fn is_transfer_fee_carried(&self) -> bool {
+        self.transfer_fee_mode == u8::from(TransferFeeMode::Carried)
+    }
+
+    pub fn reserve_transfer_fee(&mut self, amount: u64) -> Result<()> {
+        self.transfer_fee_reserve = self.transfer_fee_reserve.safe_add(amount)?;
+        Ok(())
+    }
+
+    /// Pay transfer fee on claim from reserve, and return the carried amount. Transfer fee
+    /// rate can be raised after funding, so the carried amount is capped by the reserve
+    pub fn carry_transfer_fee(&mut self, transfer_fee: u64) -> Result<u64> {
+        let carried_transfer_fee = transfer_fee.min(self.transfer_fee_reserve);
+        self.transfer_fee_reserve = self.transfer_fee_reserve.safe_sub(carried_transfer_fee)?;
+        Ok(carried_transfer_fee)
+    }
+
+    pub fn validate_and_claim_fee(
+        &mut self,
+        index: u8,
+        signer: &Pubkey,
+        current_timestamp: u64,
+    ) -> Result<u64> {
+        require!(self.is_frozen == 0, FeeVaultError::VaultFrozen);
+        self.update(current_timestamp)?;
+
         let user = self
             .users
             .get_mut(index as usize)
-            .ok_or_else(|| FeeVaultError::InvalidUserIndex)?;
+            .ok_or(FeeVaultError::InvalidUserIndex)?;
         require!(user.address.eq(signer), FeeVaultError::InvalidUserAddress);
 
-        let reward_per_share_delta = self.fee_per_share.safe_sub(user.fee_per_share_checkpoint)?;
+        // expired share only keeps the fee accrued before its end_timestamp
+        let mut fee_being_claimed = user.pending_fee;
+        user.pending_fee = 0;
 
-        let fee_being_claimed = mul_shr(user.share.into(), reward_per_share_delta, PRECISION_SCALE)
-            .ok_or_else(|| FeeVaultError::MathOverflow)?
-            .try_into()
-            .map_err(|_| FeeVaultError::MathOverflow)?;
+        if user.is_expired == 0 {
+            let reward_per_share_delta =
+                self.fee_per_share.safe_sub(user.fee_per_share_checkpoint)?;
+
+            let accrued_fee: u64 =
+                mul_shr(user.share.into(), reward_per_share_delta, PRECISION_SCALE)
+                    .ok_or(FeeVaultError::MathOverflow)?
+                    .try_into()
+                    .map_err(|_| FeeVaultError::MathOverflow)?;
+
+            user.fee_per_share_checkpoint = self.fee_per_share;
+            fee_being_claimed = fee_being_claimed.safe_add(accrued_fee)?;
+        }
 
-        user.fee_per_share_checkpoint = self.fee_per_share;
         user.fee_claimed = user.fee_claimed.safe_add(fee_being_claimed)?;
 
         Ok(fee_being_claimed)
     }
 
+    /// Only fee vault pda is able to sign as owner of a damm v2 position
+    pub fn validate_position_owner(&self) -> Result<()> {
+        require!(
+            self.fee_vault_type == u8::from(FeeVaultType::PdaAccount),
+            FeeVaultError::InvalidFeeVault
+        );
+        Ok(())
+    }
+
+    /// Share holders are stored from the first slot, empty slots have default address
+    pub fn number_of_user(&self) -> usize {
+        self.users
+            .iter()
+            .take_while(|share_holder| share_holder.address.ne(&Pubkey::default()))
+            .count()
+    }
+
     pub fn is_share_holder(&self, signer: &Pubkey) -> bool {
         self.users
             .iter()
             .any(|share_holder| share_holder.address.eq(signer))
     }
 }
+
+/// Load fee vault in current layout. Vault of an older version is shorter than current layout,
+/// which `AccountLoader::load` panics on, so it is rejected until it is migrated. A vault of
+/// current size is still rejected when its version is not the current one
+pub fn load_fee_vault<'a>(fee_vault: &'a AccountLoader<FeeVault>) -> Result<Ref<'a, FeeVault>> {
+    require!(
+        fee_vault.as_ref().data_len() == 8 + FeeVault::INIT_SPACE,
+        FeeVaultError::InvalidFeeVaultVersion
+    );
+    let fee_vault = fee_vault.load()?;
+    require!(
+        fee_vault.version == FEE_VAULT_VERSION,
+        FeeVaultError::InvalidFeeVaultVersion
+    );
+    Ok(fee_vault)
+}
@@ -0,0 +1,39 @@
+use crate::{
+    constants::{MAX_METADATA_LABEL_LEN, MAX_METADATA_NAME_LEN, MAX_METADATA_URI_LEN, MAX_USER},
+    instructions::FeeVaultMetadataParameters,
+};
+use anchor_lang::prelude::*;
+use static_assertions::const_assert_eq;
+
+/// Human readable description of a fee vault, strings are utf8 padded with zero bytes
+#[account(zero_copy)]
+#[derive(InitSpace, Debug)]
+pub struct FeeVaultMetadata {
+    pub fee_vault: Pubkey,
+    pub name: [u8; MAX_METADATA_NAME_LEN],
+    pub uri: [u8; MAX_METADATA_URI_LEN],
+    pub labels: [[u8; MAX_METADATA_LABEL_LEN]; MAX_USER], // label of share holder at same index in fee vault
+    pub padding: [u64; 8],
+}
+const_assert_eq!(FeeVaultMetadata::INIT_SPACE, 416);
+
+impl FeeVaultMetadata {
+    pub fn initialize(&mut self, fee_vault: &Pubkey, params: &FeeVaultMetadataParameters) {
+        self.fee_vault = *fee_vault;
+        self.update(params);
+    }
+
+    /// Overwrite every field, label of slot not in params is cleared
+    pub fn update(&mut self, params: &FeeVaultMetadataParameters) {
+        write_padded(&mut self.name, &params.name);
+        write_padded(&mut self.uri, &params.uri);
+        for (i, label) in self.labels.iter_mut().enumerate() {
+            write_padded(label, params.labels.get(i).map_or("", String::as_str));
+        }
+    }
+}
+
+fn write_padded(dst: &mut [u8], src: &str) {
+    dst.fill(0);
+    dst[..src.len()].copy_from_slice(src.as_bytes());
+}
@@ -0,0 +1,93 @@
+use crate::{
+    constants::{FEE_VAULT_VERSION, MAX_USER},
+    state::{FeeVault, UserFee},
+};
+use anchor_lang::prelude::*;
+use static_assertions::const_assert_eq;
+
+/// Layout of fee vault before versioning, only used to migrate existing accounts
+#[zero_copy]
+#[derive(InitSpace, Debug, Default)]
+pub struct FeeVaultV0 {
+    pub owner: Pubkey,
+    pub token_mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub token_flag: u8,
+    pub fee_vault_type: u8,
+    pub fee_vault_bump: u8,
+    pub is_frozen: u8,
+    pub is_funding_frozen: u8,
+    pub transfer_fee_mode: u8,
+    pub padding_0: [u8; 10],
+    pub total_share: u32,
+    pub padding_1: [u8; 4],
+    pub total_funded_fee: u64,
+    pub fee_per_share: u128,
+    pub base: Pubkey,
+    pub vesting_duration: u64,
+    pub vesting_end_timestamp: u64,
+    pub last_updated_timestamp: u64,
+    pub locked_fee: u64,
+    pub transfer_fee_reserve: u64,
+    pub padding_2: [u8; 8],
+    pub padding: [u128; 1],
+    pub users: [UserFeeV0; MAX_USER],
+}
+const_assert_eq!(FeeVaultV0::INIT_SPACE, 640);
+
+#[zero_copy]
+#[derive(InitSpace, Debug, Default)]
+pub struct UserFeeV0 {
+    pub address: Pubkey,
+    pub share: u32,
+    pub is_expired: u8,
+    pub padding_0: [u8; 3],
+    pub fee_claimed: u64,
+    pub end_timestamp: u64,
+    pub pending_fee: u64,
+    pub fee_per_share_checkpoint: u128,
+}
+const_assert_eq!(UserFeeV0::INIT_SPACE, 80);
+
+impl FeeVaultV0 {
+    /// Bring fee vault to current layout, state and user checkpoints are kept as is
+    pub fn migrate(&self) -> FeeVault {
+        let mut fee_vault = FeeVault {
+            owner: self.owner,
+            token_mint: self.token_mint,
+            token_vault: self.token_vault,
+            token_flag: self.token_flag,
+            fee_vault_type: self.fee_vault_type,
+            fee_vault_bump: self.fee_vault_bump,
+            is_frozen: self.is_frozen,
+            is_funding_frozen: self.is_funding_frozen,
+            transfer_fee_mode: self.transfer_fee_mode,
+            version: FEE_VAULT_VERSION,
+            total_share: self.total_share,
+            total_funded_fee: self.total_funded_fee,
+            fee_per_share: self.fee_per_share,
+            base: self.base,
+            vesting_duration: self.vesting_duration,
+            vesting_end_timestamp: self.vesting_end_timestamp,
+            last_updated_timestamp: self.last_updated_timestamp,
+            locked_fee: self.locked_fee,
+            transfer_fee_reserve: self.transfer_fee_reserve,
+            ..Default::default()
+        };
+
+        for (user, user_v0) in fee_vault.users.iter_mut().zip(self.users.iter()) {
+            *user = UserFee {
+                address: user_v0.address,
+                share: user_v0.share,
+                is_expired: user_v0.is_expired,
+                fee_claimed: user_v0.fee_claimed,
+                end_timestamp: user_v0.end_timestamp,
+                pending_fee: user_v0.pending_fee,
+                fee_per_share_checkpoint: user_v0.fee_per_share_checkpoint,
+                ..Default::default()
+            };
+        }
+
+        fee_vault
+    }
+}
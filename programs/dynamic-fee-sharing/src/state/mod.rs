@@ -1,2 +1,8 @@
 pub mod fee_vault;
 pub use fee_vault::*;
+pub mod config;
+pub use config::*;
+pub mod fee_vault_v0;
+pub use fee_vault_v0::*;
+pub mod fee_vault_metadata;
+pub use fee_vault_metadata::*;
@@ -0,0 +1,65 @@
+use crate::constants::{MAX_METADATA_LABEL_LEN, MAX_METADATA_NAME_LEN};
+use crate::error::FeeVaultError;
+use crate::instructions::FeeVaultMetadataParameters;
+use crate::state::FeeVaultMetadata;
+use anchor_lang::prelude::*;
+use bytemuck::Zeroable;
+
+fn new_params(labels: &[&str]) -> FeeVaultMetadataParameters {
+    FeeVaultMetadataParameters {
+        name: "Partner vault".to_string(),
+        uri: "https://example.com/vault.json".to_string(),
+        labels: labels.iter().map(|label| label.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_validate_metadata() {
+    new_params(&["Partner A – marketing", "Partner B"])
+        .validate(2)
+        .unwrap();
+
+    // more labels than share holders
+    assert_eq!(
+        new_params(&["Partner A", "Partner B", "Partner C"])
+            .validate(2)
+            .unwrap_err(),
+        FeeVaultError::InvalidFeeVaultMetadata.into()
+    );
+
+    // length is counted in utf8 bytes
+    let label = "é".repeat(MAX_METADATA_LABEL_LEN / 2 + 1);
+    assert_eq!(
+        new_params(&[&label]).validate(2).unwrap_err(),
+        FeeVaultError::InvalidFeeVaultMetadata.into()
+    );
+
+    let mut params = new_params(&[]);
+    params.name = "a".repeat(MAX_METADATA_NAME_LEN + 1);
+    assert_eq!(
+        params.validate(2).unwrap_err(),
+        FeeVaultError::InvalidFeeVaultMetadata.into()
+    );
+}
+
+#[test]
+fn test_update_metadata_overwrites_every_field() {
+    let fee_vault = Pubkey::new_unique();
+    let mut metadata = FeeVaultMetadata::zeroed();
+    metadata.initialize(
+        &fee_vault,
+        &new_params(&["Partner A – marketing", "Partner B"]),
+    );
+    assert_eq!(metadata.fee_vault, fee_vault);
+    assert!(metadata.labels[1].starts_with(b"Partner B\0"));
+
+    let mut params = new_params(&["Team"]);
+    params.name = "Vault".to_string();
+    metadata.update(&params);
+
+    assert!(metadata.name.starts_with(b"Vault\0"));
+    assert!(metadata.name[5..].iter().all(|byte| *byte == 0));
+    assert!(metadata.labels[0].starts_with(b"Team\0"));
+    assert!(metadata.labels[0][4..].iter().all(|byte| *byte == 0));
+    assert!(metadata.labels[1].iter().all(|byte| *byte == 0));
+}
@@ -0,0 +1,141 @@
+use crate::instructions::UserShare;
+use crate::tests::new_fee_vault;
+use anchor_lang::prelude::*;
+
+#[test]
+fn test_funded_fee_is_released_linearly() {
+    let user = Pubkey::new_unique();
+    let mut fee_vault = new_fee_vault(
+        &[
+            UserShare {
+                address: user,
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: Pubkey::new_unique(),
+                share: 100,
+                end_timestamp: 0,
+            },
+        ],
+        1_000,
+    );
+
+    fee_vault.fund_fee(10_000, 1_000).unwrap();
+    assert_eq!(fee_vault.fee_per_share, 0);
+    assert_eq!(fee_vault.locked_fee, 10_000);
+    assert_eq!(fee_vault.total_funded_fee, 10_000);
+
+    let claimed = fee_vault.validate_and_claim_fee(0, &user, 1_250).unwrap();
+    assert_eq!(claimed, 1_250);
+    assert_eq!(fee_vault.locked_fee, 7_500);
+
+    let claimed = fee_vault.validate_and_claim_fee(0, &user, 5_000).unwrap();
+    assert_eq!(claimed, 3_750);
+    assert_eq!(fee_vault.locked_fee, 0);
+}
+
+#[test]
+fn test_new_funding_restarts_vesting_with_leftover() {
+    let user = Pubkey::new_unique();
+    let mut fee_vault = new_fee_vault(
+        &[
+            UserShare {
+                address: user,
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: Pubkey::new_unique(),
+                share: 100,
+                end_timestamp: 0,
+            },
+        ],
+        1_000,
+    );
+
+    fee_vault.fund_fee(10_000, 1_000).unwrap();
+    // half released, leftover 5_000 streamed together with new 5_000 over another duration
+    fee_vault.fund_fee(5_000, 1_500).unwrap();
+    assert_eq!(fee_vault.locked_fee, 10_000);
+    assert_eq!(fee_vault.vesting_end_timestamp, 2_500);
+
+    let claimed = fee_vault.validate_and_claim_fee(0, &user, 2_000).unwrap();
+    assert_eq!(claimed, 5_000);
+
+    let claimed = fee_vault.validate_and_claim_fee(0, &user, 2_500).unwrap();
+    assert_eq!(claimed, 2_500);
+}
+
+#[test]
+fn test_vested_fee_before_expiry_goes_to_expired_share() {
+    let expiring_user = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let mut fee_vault = new_fee_vault(
+        &[
+            UserShare {
+                address: expiring_user,
+                share: 100,
+                end_timestamp: 1_500,
+            },
+            UserShare {
+                address: user,
+                share: 100,
+                end_timestamp: 0,
+            },
+        ],
+        1_000,
+    );
+
+    fee_vault.fund_fee(10_000, 1_000).unwrap();
+
+    // nothing touched the vault at expiry, half of the fee was vested before that
+    let claimed = fee_vault
+        .validate_and_claim_fee(0, &expiring_user, 3_000)
+        .unwrap();
+    assert_eq!(claimed, 2_500);
+
+    let claimed = fee_vault.validate_and_claim_fee(1, &user, 3_000).unwrap();
+    assert_eq!(claimed, 7_500);
+}
+
+#[test]
+fn test_locked_fee_goes_to_last_expired_shares() {
+    let user_a = Pubkey::new_unique();
+    let user_b = Pubkey::new_unique();
+    let user_c = Pubkey::new_unique();
+    let mut fee_vault = new_fee_vault(
+        &[
+            UserShare {
+                address: user_a,
+                share: 100,
+                end_timestamp: 1_200,
+            },
+            UserShare {
+                address: user_b,
+                share: 100,
+                end_timestamp: 1_500,
+            },
+            UserShare {
+                address: user_c,
+                share: 200,
+                end_timestamp: 1_500,
+            },
+        ],
+        1_000,
+    );
+
+    fee_vault.fund_fee(10_000, 1_000).unwrap();
+
+    // every share expired with 5_000 still locked, it is paid to shares expiring last
+    let claimed = fee_vault.validate_and_claim_fee(0, &user_a, 3_000).unwrap();
+    assert_eq!(claimed, 500);
+    assert_eq!(fee_vault.locked_fee, 0);
+    assert_eq!(fee_vault.total_share, 0);
+
+    let claimed = fee_vault.validate_and_claim_fee(1, &user_b, 3_000).unwrap();
+    assert_eq!(claimed, 500 + 1_000 + 5_000 / 3);
+
+    let claimed = fee_vault.validate_and_claim_fee(2, &user_c, 3_000).unwrap();
+    assert_eq!(claimed, 1_000 + 2_000 + 5_000 * 2 / 3);
+}
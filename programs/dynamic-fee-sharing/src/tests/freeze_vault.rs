@@ -0,0 +1,76 @@
+use crate::error::FeeVaultError;
+use crate::instructions::UserShare;
+use crate::tests::new_fee_vault;
+use anchor_lang::prelude::*;
+
+#[test]
+fn test_frozen_vault_rejects_claim_but_keeps_accruing() {
+    let user_a = Pubkey::new_unique();
+    let user_b = Pubkey::new_unique();
+    let mut fee_vault = new_fee_vault(
+        &[
+            UserShare {
+                address: user_a,
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: user_b,
+                share: 100,
+                end_timestamp: 0,
+            },
+        ],
+        1_000,
+    );
+
+    fee_vault.fund_fee(1_000, 0).unwrap();
+    fee_vault.freeze(false);
+
+    assert_eq!(
+        fee_vault
+            .validate_and_claim_fee(0, &user_a, 500)
+            .unwrap_err(),
+        FeeVaultError::VaultFrozen.into()
+    );
+
+    // funding is still allowed and vesting keeps releasing while frozen
+    fee_vault.fund_fee(1_000, 1_000).unwrap();
+    fee_vault.update(2_000).unwrap();
+    assert_eq!(fee_vault.total_funded_fee, 2_000);
+    assert_eq!(fee_vault.locked_fee, 0);
+
+    fee_vault.unfreeze();
+    let claimed = fee_vault.validate_and_claim_fee(0, &user_a, 2_000).unwrap();
+    assert_eq!(claimed, 1_000);
+    let claimed = fee_vault.validate_and_claim_fee(1, &user_b, 2_000).unwrap();
+    assert_eq!(claimed, 1_000);
+}
+
+#[test]
+fn test_frozen_vault_rejects_funding_when_requested() {
+    let mut fee_vault = new_fee_vault(
+        &[
+            UserShare {
+                address: Pubkey::new_unique(),
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: Pubkey::new_unique(),
+                share: 100,
+                end_timestamp: 0,
+            },
+        ],
+        0,
+    );
+
+    fee_vault.freeze(true);
+    assert_eq!(
+        fee_vault.fund_fee(1_000, 0).unwrap_err(),
+        FeeVaultError::VaultFrozen.into()
+    );
+
+    fee_vault.unfreeze();
+    fee_vault.fund_fee(1_000, 0).unwrap();
+    assert_eq!(fee_vault.total_funded_fee, 1_000);
+}
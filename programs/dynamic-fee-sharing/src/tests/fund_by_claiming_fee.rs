@@ -0,0 +1,387 @@
+use crate::constants::WHITELISTED_ACTIONS;
+use crate::error::FeeVaultError;
+use crate::get_whitelisted_action;
+use crate::instructions::TokenVaultSnapshot;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::Discriminator;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::TokenAccount;
+
+struct TestAccount {
+    key: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+impl TestAccount {
+    fn new(key: Pubkey) -> Self {
+        Self {
+            key,
+            lamports: 0,
+            data: vec![],
+        }
+    }
+}
+
+fn to_account_infos(accounts: &mut [TestAccount]) -> Vec<AccountInfo<'_>> {
+    accounts
+        .iter_mut()
+        .map(|account| {
+            AccountInfo::new(
+                &account.key,
+                false,
+                true,
+                &mut account.lamports,
+                &mut account.data,
+                &crate::ID,
+                false,
+                0,
+            )
+        })
+        .collect()
+}
+
+// claim_position_fee: token_vault at 4, fee vault (owner) at 10
+fn claim_position_fee_accounts(fee_vault: Pubkey, token_vault: Pubkey) -> Vec<TestAccount> {
+    (0..15)
+        .map(|index| match index {
+            4 => TestAccount::new(token_vault),
+            10 => TestAccount::new(fee_vault),
+            _ => TestAccount::new(Pubkey::new_unique()),
+        })
+        .collect()
+}
+
+#[test]
+fn test_validate_claim_position_fee() {
+    let action = &WHITELISTED_ACTIONS[0];
+    let fee_vault = Pubkey::new_unique();
+    let token_vault = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    let mut accounts = claim_position_fee_accounts(fee_vault, token_vault);
+    let account_infos = to_account_infos(&mut accounts);
+
+    action
+        .validate(
+            action.discriminator,
+            &fee_vault,
+            &token_vault,
+            &token_mint,
+            &account_infos,
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_to_instruction_signs_only_fee_vault_slot() {
+    for action in WHITELISTED_ACTIONS.iter() {
+        let fee_vault = Pubkey::new_unique();
+        let mut accounts: Vec<TestAccount> = (0..action.number_of_accounts)
+            .map(|index| {
+                TestAccount::new(if index == action.fee_vault_index {
+                    fee_vault
+                } else {
+                    Pubkey::new_unique()
+                })
+            })
+            .collect();
+        // signature of any remaining account, such as the funder, is never forwarded to source
+        // program, and writable flags are kept as passed
+        let account_infos: Vec<AccountInfo> = to_account_infos(&mut accounts)
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut account)| {
+                account.is_signer = true;
+                account.is_writable = index % 2 == 0;
+                account
+            })
+            .collect();
+
+        let instruction = action.to_instruction(action.discriminator.to_vec(), &account_infos);
+
+        assert_eq!(instruction.program_id, action.program_id);
+        assert_eq!(instruction.data, action.discriminator);
+        let signers: Vec<Pubkey> = instruction
+            .accounts
+            .iter()
+            .filter(|meta| meta.is_signer)
+            .map(|meta| meta.pubkey)
+            .collect();
+        assert_eq!(signers, vec![fee_vault]);
+        for (index, meta) in instruction.accounts.iter().enumerate() {
+            assert_eq!(meta.pubkey, *account_infos[index].key);
+            assert_eq!(meta.is_writable, index % 2 == 0);
+        }
+    }
+}
+
+#[test]
+fn test_validate_invalid_payload_length() {
+    let action = &WHITELISTED_ACTIONS[0];
+    let fee_vault = Pubkey::new_unique();
+    let token_vault = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    let mut accounts = claim_position_fee_accounts(fee_vault, token_vault);
+    let account_infos = to_account_infos(&mut accounts);
+
+    let payload = [action.discriminator, &[0u8]].concat();
+    let err = action
+        .validate(
+            &payload,
+            &fee_vault,
+            &token_vault,
+            &token_mint,
+            &account_infos,
+        )
+        .unwrap_err();
+    assert_eq!(err, FeeVaultError::InvalidPayloadLength.into());
+}
+
+#[test]
+fn test_validate_invalid_number_of_accounts() {
+    let action = &WHITELISTED_ACTIONS[0];
+    let fee_vault = Pubkey::new_unique();
+    let token_vault = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    let mut accounts = claim_position_fee_accounts(fee_vault, token_vault);
+    accounts.push(TestAccount::new(Pubkey::new_unique()));
+    let account_infos = to_account_infos(&mut accounts);
+
+    let err = action
+        .validate(
+            action.discriminator,
+            &fee_vault,
+            &token_vault,
+            &token_mint,
+            &account_infos,
+        )
+        .unwrap_err();
+    assert_eq!(err, FeeVaultError::InvalidNumberOfAccounts.into());
+}
+
+#[test]
+fn test_validate_fee_vault_outside_signer_slot() {
+    let action = &WHITELISTED_ACTIONS[0];
+    let fee_vault = Pubkey::new_unique();
+    let token_vault = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+
+    // fee vault passed as another account
+    let mut accounts = claim_position_fee_accounts(fee_vault, token_vault);
+    accounts[3] = TestAccount::new(fee_vault);
+    let account_infos = to_account_infos(&mut accounts);
+    let err = action
+        .validate(
+            action.discriminator,
+            &fee_vault,
+            &token_vault,
+            &token_mint,
+            &account_infos,
+        )
+        .unwrap_err();
+    assert_eq!(err, FeeVaultError::InvalidFeeVaultPosition.into());
+
+    // fee vault missing from signer slot
+    let mut accounts = claim_position_fee_accounts(fee_vault, token_vault);
+    accounts[10] = TestAccount::new(Pubkey::new_unique());
+    let account_infos = to_account_infos(&mut accounts);
+    let err = action
+        .validate(
+            action.discriminator,
+            &fee_vault,
+            &token_vault,
+            &token_mint,
+            &account_infos,
+        )
+        .unwrap_err();
+    assert_eq!(err, FeeVaultError::InvalidFeeVaultPosition.into());
+}
+
+#[test]
+fn test_whitelisted_actions_layout() {
+    for action in WHITELISTED_ACTIONS.iter() {
+        assert!(action.payload_len >= action.discriminator.len());
+        assert!(action.receiver_index < action.number_of_accounts);
+        assert!(action.fee_vault_index < action.number_of_accounts);
+        assert_ne!(action.receiver_index, action.fee_vault_index);
+        assert!(get_whitelisted_action(&action.program_id, action.discriminator).is_some());
+    }
+}
+
+#[test]
+fn test_validate_withdraw_leftover_to_fee_vault_ata() {
+    let action = get_whitelisted_action(
+        &dynamic_bonding_curve::ID,
+        dynamic_bonding_curve::client::args::WithdrawLeftover::DISCRIMINATOR,
+    )
+    .unwrap();
+    let fee_vault = Pubkey::new_unique();
+    let token_vault = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    let fee_vault_ata =
+        get_associated_token_address_with_program_id(&fee_vault, &token_mint, &Token2022::id());
+
+    let new_accounts = |receiver: Pubkey| -> Vec<TestAccount> {
+        (0..10)
+            .map(|index| match index {
+                3 => TestAccount::new(receiver),
+                5 => TestAccount::new(token_mint),
+                6 => TestAccount::new(fee_vault),
+                7 => TestAccount::new(Token2022::id()),
+                _ => TestAccount::new(Pubkey::new_unique()),
+            })
+            .collect()
+    };
+
+    let mut accounts = new_accounts(fee_vault_ata);
+    let account_infos = to_account_infos(&mut accounts);
+    action
+        .validate(
+            action.discriminator,
+            &fee_vault,
+            &token_vault,
+            &token_mint,
+            &account_infos,
+        )
+        .unwrap();
+
+    // leftover can't be withdrawn to token vault directly
+    let mut accounts = new_accounts(token_vault);
+    let account_infos = to_account_infos(&mut accounts);
+    let err = action
+        .validate(
+            action.discriminator,
+            &fee_vault,
+            &token_vault,
+            &token_mint,
+            &account_infos,
+        )
+        .unwrap_err();
+    assert_eq!(err, FeeVaultError::InvalidAction.into());
+}
+
+#[test]
+fn test_only_claim_position_fee_can_swap() {
+    let swappable_actions: Vec<usize> = WHITELISTED_ACTIONS
+        .iter()
+        .enumerate()
+        .filter(|(_, action)| action.is_claim_position_fee())
+        .map(|(index, _)| index)
+        .collect();
+    assert_eq!(swappable_actions, vec![0]);
+
+    // swap accounts are taken from claim_position_fee accounts
+    let action = &WHITELISTED_ACTIONS[0];
+    assert_eq!(
+        action.receiver_index,
+        crate::claim_position_fee_accounts::TOKEN_B_ACCOUNT
+    );
+    assert_eq!(
+        action.fee_vault_index,
+        crate::claim_position_fee_accounts::OWNER
+    );
+    assert_eq!(
+        action.number_of_accounts,
+        crate::claim_position_fee_accounts::PROGRAM + 1
+    );
+}
+
+fn new_token_vault(amount: u64) -> spl_token_2022::state::Account {
+    spl_token_2022::state::Account {
+        mint: Pubkey::new_unique(),
+        owner: crate::const_pda::fee_vault_authority::ID,
+        amount,
+        state: spl_token_2022::state::AccountState::Initialized,
+        ..Default::default()
+    }
+}
+
+fn to_token_account(token_vault: spl_token_2022::state::Account) -> TokenAccount {
+    let mut data = vec![0u8; spl_token_2022::state::Account::LEN];
+    spl_token_2022::state::Account::pack(token_vault, &mut data).unwrap();
+    TokenAccount::try_deserialize(&mut &data[..]).unwrap()
+}
+
+fn get_claimed_amount(
+    before: spl_token_2022::state::Account,
+    after: spl_token_2022::state::Account,
+) -> Result<u64> {
+    TokenVaultSnapshot::new(&to_token_account(before)).get_claimed_amount(&to_token_account(after))
+}
+
+#[test]
+fn test_claimed_amount_is_token_vault_increase() {
+    let before = new_token_vault(1_000);
+
+    let after = spl_token_2022::state::Account {
+        amount: 1_500,
+        ..before
+    };
+    assert_eq!(get_claimed_amount(before, after).unwrap(), 500);
+    assert_eq!(get_claimed_amount(before, before).unwrap(), 0);
+
+    let after = spl_token_2022::state::Account {
+        amount: u64::MAX,
+        ..before
+    };
+    assert_eq!(get_claimed_amount(before, after).unwrap(), u64::MAX - 1_000);
+}
+
+#[test]
+fn test_claimed_amount_rejects_token_vault_decrease() {
+    let before = new_token_vault(1_000);
+
+    for amount in [999, 0] {
+        let after = spl_token_2022::state::Account { amount, ..before };
+        assert_eq!(
+            get_claimed_amount(before, after).unwrap_err(),
+            FeeVaultError::TokenVaultBalanceDecreased.into()
+        );
+    }
+}
+
+#[test]
+fn test_claimed_amount_rejects_token_vault_authority_change() {
+    let before = new_token_vault(1_000);
+    let attacker = Pubkey::new_unique();
+
+    let changed_authorities = [
+        // rejected even though balance increases
+        spl_token_2022::state::Account {
+            owner: attacker,
+            amount: 1_500,
+            ..before
+        },
+        spl_token_2022::state::Account {
+            delegate: COption::Some(attacker),
+            delegated_amount: u64::MAX,
+            ..before
+        },
+        spl_token_2022::state::Account {
+            close_authority: COption::Some(attacker),
+            ..before
+        },
+    ];
+    for after in changed_authorities {
+        assert_eq!(
+            get_claimed_amount(before, after).unwrap_err(),
+            FeeVaultError::TokenVaultAuthorityChanged.into()
+        );
+    }
+
+    // authority already set before cpi is kept
+    let before = spl_token_2022::state::Account {
+        delegate: COption::Some(attacker),
+        close_authority: COption::Some(attacker),
+        ..before
+    };
+    let after = spl_token_2022::state::Account {
+        amount: 1_200,
+        ..before
+    };
+    assert_eq!(get_claimed_amount(before, after).unwrap(), 200);
+}
@@ -15,7 +15,7 @@ proptest! {
             ..Default::default()
         };
 
-        fee_vault.fund_fee(amount).unwrap();
+        fee_vault.fund_fee(amount, 0).unwrap();
 
         assert!(fee_vault.fee_per_share > 0);
     }
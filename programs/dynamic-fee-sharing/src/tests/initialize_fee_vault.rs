@@ -0,0 +1,169 @@
+use crate::constants::MAX_VESTING_DURATION;
+use crate::error::FeeVaultError;
+use crate::instructions::{DuplicateUserMode, InitializeFeeVaultParameters, UserShare};
+use anchor_lang::prelude::*;
+
+fn new_params(
+    duplicate_user_mode: DuplicateUserMode,
+    users: Vec<UserShare>,
+) -> InitializeFeeVaultParameters {
+    InitializeFeeVaultParameters {
+        vesting_duration: 0,
+        duplicate_user_mode: duplicate_user_mode.into(),
+        transfer_fee_mode: 0,
+        allow_permanent_delegate: 0,
+        padding_0: [0; 5],
+        padding: [0; 6],
+        users,
+        metadata: None,
+    }
+}
+
+#[test]
+fn test_reject_duplicated_user() {
+    let user = Pubkey::new_unique();
+    let params = new_params(
+        DuplicateUserMode::Reject,
+        vec![
+            UserShare {
+                address: user,
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: Pubkey::new_unique(),
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: user,
+                share: 50,
+                end_timestamp: 0,
+            },
+        ],
+    );
+
+    let err = params.validate(0).unwrap_err();
+    assert_eq!(err, FeeVaultError::DuplicatedUser.into());
+}
+
+#[test]
+fn test_merge_duplicated_user() {
+    let user = Pubkey::new_unique();
+    let other_user = Pubkey::new_unique();
+    let params = new_params(
+        DuplicateUserMode::Merge,
+        vec![
+            UserShare {
+                address: user,
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: other_user,
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: user,
+                share: 50,
+                end_timestamp: 0,
+            },
+        ],
+    );
+
+    let users = params.validate(0).unwrap();
+    assert_eq!(users.len(), 2);
+    assert_eq!(users[0].address, user);
+    assert_eq!(users[0].share, 150);
+    assert_eq!(users[1].address, other_user);
+    assert_eq!(users[1].share, 100);
+}
+
+#[test]
+fn test_merge_duplicated_user_with_different_end_timestamp() {
+    let user = Pubkey::new_unique();
+    let params = new_params(
+        DuplicateUserMode::Merge,
+        vec![
+            UserShare {
+                address: user,
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: Pubkey::new_unique(),
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: user,
+                share: 50,
+                end_timestamp: 1_000,
+            },
+        ],
+    );
+
+    let err = params.validate(0).unwrap_err();
+    assert_eq!(err, FeeVaultError::DuplicatedUser.into());
+}
+
+#[test]
+fn test_merge_into_single_user() {
+    let user = Pubkey::new_unique();
+    let params = new_params(
+        DuplicateUserMode::Merge,
+        vec![
+            UserShare {
+                address: user,
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: user,
+                share: 100,
+                end_timestamp: 0,
+            },
+        ],
+    );
+
+    let err = params.validate(0).unwrap_err();
+    assert_eq!(err, FeeVaultError::ExceededUser.into());
+}
+
+#[test]
+fn test_reject_vesting_duration_above_max() {
+    let users = vec![
+        UserShare {
+            address: Pubkey::new_unique(),
+            share: 100,
+            end_timestamp: 0,
+        },
+        UserShare {
+            address: Pubkey::new_unique(),
+            share: 100,
+            end_timestamp: 0,
+        },
+    ];
+
+    let mut params = new_params(DuplicateUserMode::Reject, users);
+    params.vesting_duration = MAX_VESTING_DURATION;
+    assert!(params.validate(0).is_ok());
+
+    params.vesting_duration = MAX_VESTING_DURATION + 1;
+    let err = params.validate(0).unwrap_err();
+    assert_eq!(err, FeeVaultError::InvalidFeeVaultParameters.into());
+}
+
+#[test]
+fn test_permanent_delegate_requires_owner_signature() {
+    let mut params = new_params(DuplicateUserMode::Reject, vec![]);
+    params.validate_owner_approval(false).unwrap();
+
+    params.allow_permanent_delegate = 1;
+    params.validate_owner_approval(true).unwrap();
+    assert_eq!(
+        params.validate_owner_approval(false).unwrap_err(),
+        FeeVaultError::PermanentDelegateNotApprovedByOwner.into()
+    );
+}
@@ -0,0 +1,223 @@
+use crate::error::FeeVaultError;
+use crate::instructions::{LockPositionCtx, PermanentLockPositionCtx, RefreshVestingCtx};
+use crate::state::{FeeVault, FeeVaultType};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::Discriminator;
+
+struct TestAccount {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+    executable: bool,
+}
+
+impl TestAccount {
+    fn new(is_signer: bool) -> Self {
+        Self {
+            key: Pubkey::new_unique(),
+            owner: Pubkey::default(),
+            lamports: 0,
+            data: vec![],
+            is_signer,
+            executable: false,
+        }
+    }
+
+    fn fee_vault() -> Self {
+        let mut data = FeeVault::DISCRIMINATOR.to_vec();
+        data.resize(8 + FeeVault::INIT_SPACE, 0);
+        Self {
+            owner: crate::ID,
+            data,
+            ..Self::new(false)
+        }
+    }
+
+    fn program(key: Pubkey) -> Self {
+        Self {
+            key,
+            executable: true,
+            ..Self::new(false)
+        }
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            true,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            self.executable,
+            0,
+        )
+    }
+}
+
+fn keys(metas: &[AccountMeta]) -> Vec<(Pubkey, bool, bool)> {
+    metas
+        .iter()
+        .map(|meta| (meta.pubkey, meta.is_signer, meta.is_writable))
+        .collect()
+}
+
+#[test]
+fn test_only_fee_vault_pda_manages_position() {
+    let mut fee_vault = FeeVault {
+        fee_vault_type: FeeVaultType::PdaAccount.into(),
+        ..Default::default()
+    };
+    fee_vault.validate_position_owner().unwrap();
+
+    // lock_position, permanent_lock_position and refresh_vesting are rejected for keypair vault
+    fee_vault.fee_vault_type = FeeVaultType::NonPdaAccount.into();
+    assert_eq!(
+        fee_vault.validate_position_owner().unwrap_err(),
+        FeeVaultError::InvalidFeeVault.into()
+    );
+}
+
+#[test]
+fn test_lock_position_cpi_accounts() {
+    let mut fee_vault = TestAccount::fee_vault();
+    let mut pool = TestAccount::new(false);
+    let mut position = TestAccount::new(false);
+    let mut vesting = TestAccount::new(true);
+    let mut position_nft_account = TestAccount::new(false);
+    let mut owner = TestAccount::new(true);
+    let mut payer = TestAccount::new(true);
+    let mut system_program = TestAccount::program(System::id());
+    let mut damm_v2_event_authority = TestAccount::new(false);
+    let mut damm_v2_program = TestAccount::program(damm_v2::ID);
+    let mut event_authority = TestAccount::new(false);
+    let mut program = TestAccount::program(crate::ID);
+
+    let fee_vault = fee_vault.info();
+    let pool = pool.info();
+    let position = position.info();
+    let vesting = vesting.info();
+    let position_nft_account = position_nft_account.info();
+    let owner = owner.info();
+    let payer = payer.info();
+    let system_program = system_program.info();
+    let damm_v2_event_authority = damm_v2_event_authority.info();
+    let damm_v2_program = damm_v2_program.info();
+
+    let ctx = LockPositionCtx {
+        fee_vault: AccountLoader::try_from(&fee_vault).unwrap(),
+        pool: UncheckedAccount::try_from(&pool),
+        position: UncheckedAccount::try_from(&position),
+        vesting: Signer::try_from(&vesting).unwrap(),
+        position_nft_account: UncheckedAccount::try_from(&position_nft_account),
+        owner: Signer::try_from(&owner).unwrap(),
+        payer: Signer::try_from(&payer).unwrap(),
+        system_program: Program::try_from(&system_program).unwrap(),
+        damm_v2_event_authority: UncheckedAccount::try_from(&damm_v2_event_authority),
+        damm_v2_program: UncheckedAccount::try_from(&damm_v2_program),
+        event_authority: event_authority.info(),
+        program: program.info(),
+    };
+
+    // fee vault replaces owner as the signing position owner, owner itself is not passed
+    assert_eq!(
+        keys(&ctx.damm_v2_accounts().to_account_metas(None)),
+        vec![
+            (*pool.key, false, false),
+            (*position.key, false, true),
+            (*vesting.key, true, true),
+            (*position_nft_account.key, false, false),
+            (*fee_vault.key, true, false),
+            (*payer.key, true, true),
+            (*system_program.key, false, false),
+            (*damm_v2_event_authority.key, false, false),
+            (*damm_v2_program.key, false, false),
+        ]
+    );
+}
+
+#[test]
+fn test_permanent_lock_position_cpi_accounts() {
+    let mut fee_vault = TestAccount::fee_vault();
+    let mut pool = TestAccount::new(false);
+    let mut position = TestAccount::new(false);
+    let mut position_nft_account = TestAccount::new(false);
+    let mut owner = TestAccount::new(true);
+    let mut damm_v2_event_authority = TestAccount::new(false);
+    let mut damm_v2_program = TestAccount::program(damm_v2::ID);
+    let mut event_authority = TestAccount::new(false);
+    let mut program = TestAccount::program(crate::ID);
+
+    let fee_vault = fee_vault.info();
+    let pool = pool.info();
+    let position = position.info();
+    let position_nft_account = position_nft_account.info();
+    let owner = owner.info();
+    let damm_v2_event_authority = damm_v2_event_authority.info();
+    let damm_v2_program = damm_v2_program.info();
+
+    let ctx = PermanentLockPositionCtx {
+        fee_vault: AccountLoader::try_from(&fee_vault).unwrap(),
+        pool: UncheckedAccount::try_from(&pool),
+        position: UncheckedAccount::try_from(&position),
+        position_nft_account: UncheckedAccount::try_from(&position_nft_account),
+        owner: Signer::try_from(&owner).unwrap(),
+        damm_v2_event_authority: UncheckedAccount::try_from(&damm_v2_event_authority),
+        damm_v2_program: UncheckedAccount::try_from(&damm_v2_program),
+        event_authority: event_authority.info(),
+        program: program.info(),
+    };
+
+    assert_eq!(
+        keys(&ctx.damm_v2_accounts().to_account_metas(None)),
+        vec![
+            (*pool.key, false, true),
+            (*position.key, false, true),
+            (*position_nft_account.key, false, false),
+            (*fee_vault.key, true, false),
+            (*damm_v2_event_authority.key, false, false),
+            (*damm_v2_program.key, false, false),
+        ]
+    );
+}
+
+#[test]
+fn test_refresh_vesting_cpi_accounts() {
+    let mut fee_vault = TestAccount::fee_vault();
+    let mut pool = TestAccount::new(false);
+    let mut position = TestAccount::new(false);
+    let mut position_nft_account = TestAccount::new(false);
+    let mut damm_v2_program = TestAccount::program(damm_v2::ID);
+    let mut event_authority = TestAccount::new(false);
+    let mut program = TestAccount::program(crate::ID);
+
+    let fee_vault = fee_vault.info();
+    let pool = pool.info();
+    let position = position.info();
+    let position_nft_account = position_nft_account.info();
+    let damm_v2_program = damm_v2_program.info();
+
+    let ctx = RefreshVestingCtx {
+        fee_vault: AccountLoader::try_from(&fee_vault).unwrap(),
+        pool: UncheckedAccount::try_from(&pool),
+        position: UncheckedAccount::try_from(&position),
+        position_nft_account: UncheckedAccount::try_from(&position_nft_account),
+        damm_v2_program: UncheckedAccount::try_from(&damm_v2_program),
+        event_authority: event_authority.info(),
+        program: program.info(),
+    };
+
+    // permissionless, fee vault is passed as position owner without signing
+    assert_eq!(
+        keys(&ctx.damm_v2_accounts().to_account_metas(None)),
+        vec![
+            (*pool.key, false, false),
+            (*position.key, false, true),
+            (*position_nft_account.key, false, false),
+            (*fee_vault.key, false, false),
+        ]
+    );
+}
@@ -0,0 +1,214 @@
+use crate::constants::{FEE_VAULT_VERSION, MAX_USER};
+use crate::error::FeeVaultError;
+use crate::instructions::{migrate_fee_vault_data, UserShare};
+use crate::state::{load_fee_vault, FeeVault, FeeVaultV0};
+use crate::tests::new_fee_vault;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+/// Fee vault layout of the program before versioning, kept apart from `FeeVaultV0` so the test
+/// doesn't share its assumptions with the migration
+#[zero_copy]
+#[derive(Default)]
+struct BaselineFeeVault {
+    owner: Pubkey,
+    token_mint: Pubkey,
+    token_vault: Pubkey,
+    token_flag: u8,
+    fee_vault_type: u8,
+    fee_vault_bump: u8,
+    padding_0: [u8; 13],
+    total_share: u32,
+    padding_1: [u8; 4],
+    total_funded_fee: u64,
+    fee_per_share: u128,
+    base: Pubkey,
+    padding: [u128; 4],
+    users: [BaselineUserFee; MAX_USER],
+}
+
+#[zero_copy]
+#[derive(Default)]
+struct BaselineUserFee {
+    address: Pubkey,
+    share: u32,
+    padding_0: [u8; 4],
+    fee_claimed: u64,
+    padding: [u8; 16],
+    fee_per_share_checkpoint: u128,
+}
+
+/// Account data of a fee vault as written by the program before versioning, which only knew
+/// share, fee_claimed and checkpoint of users
+fn to_baseline_account_data(fee_vault: &FeeVault) -> Vec<u8> {
+    let mut baseline = BaselineFeeVault {
+        owner: fee_vault.owner,
+        token_mint: fee_vault.token_mint,
+        token_vault: fee_vault.token_vault,
+        token_flag: fee_vault.token_flag,
+        fee_vault_type: fee_vault.fee_vault_type,
+        fee_vault_bump: fee_vault.fee_vault_bump,
+        total_share: fee_vault.total_share,
+        total_funded_fee: fee_vault.total_funded_fee,
+        fee_per_share: fee_vault.fee_per_share,
+        base: fee_vault.base,
+        ..Default::default()
+    };
+    for (baseline_user, user) in baseline.users.iter_mut().zip(fee_vault.users.iter()) {
+        *baseline_user = BaselineUserFee {
+            address: user.address,
+            share: user.share,
+            fee_claimed: user.fee_claimed,
+            fee_per_share_checkpoint: user.fee_per_share_checkpoint,
+            ..Default::default()
+        };
+    }
+
+    let mut data = FeeVault::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&baseline));
+    data
+}
+
+#[test]
+fn test_migrated_v0_fee_vault_claims_correctly() {
+    let users = [
+        UserShare {
+            address: Pubkey::new_unique(),
+            share: 100,
+            end_timestamp: 0,
+        },
+        UserShare {
+            address: Pubkey::new_unique(),
+            share: 300,
+            end_timestamp: 0,
+        },
+        UserShare {
+            address: Pubkey::new_unique(),
+            share: 600,
+            end_timestamp: 0,
+        },
+    ];
+    // the program before versioning had neither vesting nor share expiry
+    let mut fee_vault = new_fee_vault(&users, 0);
+    fee_vault.fund_fee(1_000_000, 0).unwrap();
+    fee_vault
+        .validate_and_claim_fee(0, &users[0].address, 0)
+        .unwrap();
+    fee_vault.fund_fee(2_000_000, 0).unwrap();
+
+    let data = to_baseline_account_data(&fee_vault);
+    assert_eq!(data.len(), 8 + 640);
+    assert_eq!(data.len(), 8 + FeeVaultV0::INIT_SPACE);
+    let mut migrated_fee_vault = migrate_fee_vault_data(&data).unwrap();
+    assert_eq!(migrated_fee_vault.version, FEE_VAULT_VERSION);
+    // checkpoints are copied as is, only version is set
+    fee_vault.version = FEE_VAULT_VERSION;
+    assert_eq!(
+        bytemuck::bytes_of(&migrated_fee_vault),
+        bytemuck::bytes_of(&fee_vault)
+    );
+
+    for (index, user) in users.iter().enumerate() {
+        let index = index as u8;
+        let claimed_fee = fee_vault
+            .validate_and_claim_fee(index, &user.address, 200)
+            .unwrap();
+        let migrated_claimed_fee = migrated_fee_vault
+            .validate_and_claim_fee(index, &user.address, 200)
+            .unwrap();
+        assert!(claimed_fee > 0);
+        assert_eq!(claimed_fee, migrated_claimed_fee);
+    }
+    assert_eq!(migrated_fee_vault.users[0].fee_claimed, 100_000 + 200_000);
+}
+
+#[test]
+fn test_reject_loading_unmigrated_fee_vault() {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut data = to_baseline_account_data(&new_fee_vault(
+        &[
+            UserShare {
+                address: Pubkey::new_unique(),
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: Pubkey::new_unique(),
+                share: 100,
+                end_timestamp: 0,
+            },
+        ],
+        0,
+    ));
+    let account_info = AccountInfo::new(
+        &key,
+        false,
+        true,
+        &mut lamports,
+        &mut data,
+        &crate::ID,
+        false,
+        0,
+    );
+    let fee_vault = AccountLoader::<FeeVault>::try_from(&account_info).unwrap();
+    assert_eq!(
+        load_fee_vault(&fee_vault).unwrap_err(),
+        FeeVaultError::InvalidFeeVaultVersion.into()
+    );
+}
+
+#[test]
+fn test_reject_loading_fee_vault_with_stale_version() {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut fee_vault = new_fee_vault(
+        &[
+            UserShare {
+                address: Pubkey::new_unique(),
+                share: 100,
+                end_timestamp: 0,
+            },
+            UserShare {
+                address: Pubkey::new_unique(),
+                share: 100,
+                end_timestamp: 0,
+            },
+        ],
+        0,
+    );
+    fee_vault.version = 0;
+    // account data is aligned on chain, fee vault after the discriminator needs 16 bytes alignment
+    let data_len = 8 + FeeVault::INIT_SPACE;
+    let mut buffer = vec![0u128; data_len / 16 + 1];
+    let data = &mut bytemuck::cast_slice_mut::<u128, u8>(&mut buffer)[8..8 + data_len];
+    data[..8].copy_from_slice(FeeVault::DISCRIMINATOR);
+    data[8..].copy_from_slice(bytemuck::bytes_of(&fee_vault));
+    let account_info =
+        AccountInfo::new(&key, false, true, &mut lamports, data, &crate::ID, false, 0);
+    let fee_vault = AccountLoader::<FeeVault>::try_from(&account_info).unwrap();
+    assert_eq!(
+        load_fee_vault(&fee_vault).unwrap_err(),
+        FeeVaultError::InvalidFeeVaultVersion.into()
+    );
+}
+
+#[test]
+fn test_reject_migrating_current_fee_vault() {
+    let fee_vault = FeeVault::default();
+    let mut data = FeeVault::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&fee_vault));
+    assert_eq!(
+        migrate_fee_vault_data(&data).unwrap_err(),
+        FeeVaultError::InvalidFeeVaultVersion.into()
+    );
+}
+
+#[test]
+fn test_reject_migrating_other_account() {
+    let data = vec![0u8; 8 + FeeVaultV0::INIT_SPACE];
+    assert_eq!(
+        migrate_fee_vault_data(&data).unwrap_err(),
+        ErrorCode::AccountDiscriminatorMismatch.into()
+    );
+}
@@ -0,0 +1,140 @@
+use crate::state::Config;
+use crate::utils::token::{is_supported_mint, MintExtensionPolicy};
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        default_account_state::DefaultAccountState, interest_bearing_mint::InterestBearingConfig,
+        mint_close_authority::MintCloseAuthority, non_transferable::NonTransferable,
+        permanent_delegate::PermanentDelegate, transfer_hook::TransferHook,
+        BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+    },
+    state::AccountState,
+};
+use anchor_spl::token_interface::Mint;
+
+fn new_mint(
+    extensions: &[ExtensionType],
+    init_extensions: impl FnOnce(&mut StateWithExtensionsMut<spl_token_2022::state::Mint>),
+) -> InterfaceAccount<'static, Mint> {
+    let len = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(extensions)
+        .unwrap();
+    let data = Box::leak(vec![0u8; len].into_boxed_slice());
+    let mut mint =
+        StateWithExtensionsMut::<spl_token_2022::state::Mint>::unpack_uninitialized(data).unwrap();
+    init_extensions(&mut mint);
+    mint.base = spl_token_2022::state::Mint {
+        decimals: 6,
+        is_initialized: true,
+        ..Default::default()
+    };
+    mint.pack_base();
+    mint.init_account_type().unwrap();
+
+    let account_info = Box::leak(Box::new(AccountInfo::new(
+        Box::leak(Box::new(Pubkey::new_unique())),
+        false,
+        false,
+        Box::leak(Box::new(1)),
+        data,
+        &spl_token_2022::ID,
+        false,
+        0,
+    )));
+    InterfaceAccount::try_from(account_info).unwrap()
+}
+
+const DEFAULT_POLICY: MintExtensionPolicy = MintExtensionPolicy {
+    config: None,
+    allow_permanent_delegate: false,
+};
+
+#[test]
+fn test_supported_extensions() {
+    let mint = new_mint(
+        &[
+            ExtensionType::InterestBearingConfig,
+            ExtensionType::MintCloseAuthority,
+        ],
+        |mint| {
+            mint.init_extension::<InterestBearingConfig>(true).unwrap();
+            mint.init_extension::<MintCloseAuthority>(true)
+                .unwrap()
+                .close_authority = Some(Pubkey::new_unique()).try_into().unwrap();
+        },
+    );
+    assert!(is_supported_mint(&mint, &DEFAULT_POLICY).unwrap());
+}
+
+#[test]
+fn test_reject_non_transferable_mint() {
+    let mint = new_mint(&[ExtensionType::NonTransferable], |mint| {
+        mint.init_extension::<NonTransferable>(true).unwrap();
+    });
+    assert!(!is_supported_mint(&mint, &DEFAULT_POLICY).unwrap());
+}
+
+#[test]
+fn test_default_account_state() {
+    for (state, is_supported) in [
+        (AccountState::Initialized, true),
+        // token vault would be frozen on creation
+        (AccountState::Frozen, false),
+    ] {
+        let mint = new_mint(&[ExtensionType::DefaultAccountState], |mint| {
+            mint.init_extension::<DefaultAccountState>(true)
+                .unwrap()
+                .state = state as u8;
+        });
+        assert_eq!(
+            is_supported_mint(&mint, &DEFAULT_POLICY).unwrap(),
+            is_supported
+        );
+    }
+}
+
+#[test]
+fn test_permanent_delegate_requires_opt_in() {
+    let mint = new_mint(&[ExtensionType::PermanentDelegate], |mint| {
+        mint.init_extension::<PermanentDelegate>(true)
+            .unwrap()
+            .delegate = Some(Pubkey::new_unique()).try_into().unwrap();
+    });
+    assert!(!is_supported_mint(&mint, &DEFAULT_POLICY).unwrap());
+    assert!(is_supported_mint(
+        &mint,
+        &MintExtensionPolicy {
+            config: None,
+            allow_permanent_delegate: true,
+        }
+    )
+    .unwrap());
+
+    // no delegate is set
+    let mint = new_mint(&[ExtensionType::PermanentDelegate], |mint| {
+        mint.init_extension::<PermanentDelegate>(true).unwrap();
+    });
+    assert!(is_supported_mint(&mint, &DEFAULT_POLICY).unwrap());
+}
+
+#[test]
+fn test_transfer_hook_program_must_be_whitelisted() {
+    let hook_program = Pubkey::new_unique();
+    let mint = new_mint(&[ExtensionType::TransferHook], |mint| {
+        mint.init_extension::<TransferHook>(true)
+            .unwrap()
+            .program_id = Some(hook_program).try_into().unwrap();
+    });
+    assert!(!is_supported_mint(&mint, &DEFAULT_POLICY).unwrap());
+
+    let mut config = Config::default();
+    config.set_transfer_hook_programs(&[hook_program]).unwrap();
+    assert!(is_supported_mint(
+        &mint,
+        &MintExtensionPolicy {
+            config: Some(&config),
+            allow_permanent_delegate: false,
+        }
+    )
+    .unwrap());
+}
@@ -1,2 +1,54 @@
 #[cfg(test)]
+mod fee_vault_metadata;
+#[cfg(test)]
+mod fee_vesting;
+#[cfg(test)]
+mod freeze_vault;
+#[cfg(test)]
+mod fund_by_claiming_fee;
+#[cfg(test)]
 mod fund_fee;
+#[cfg(test)]
+mod initialize_fee_vault;
+#[cfg(test)]
+mod lock_position;
+#[cfg(test)]
+mod migrate_fee_vault;
+#[cfg(test)]
+mod mint_extension;
+#[cfg(test)]
+mod protocol_fee;
+#[cfg(test)]
+mod share_expiry;
+#[cfg(test)]
+mod transfer_fee;
+#[cfg(test)]
+mod transfer_hook;
+
+#[cfg(test)]
+use crate::{
+    instructions::UserShare,
+    state::{FeeVault, FeeVaultKeys},
+};
+#[cfg(test)]
+use anchor_lang::prelude::*;
+
+/// Fee vault of spl token mint, initialized with share holders and vesting duration only
+#[cfg(test)]
+pub fn new_fee_vault(users: &[UserShare], vesting_duration: u64) -> FeeVault {
+    let mut fee_vault = FeeVault::default();
+    fee_vault
+        .initialize(
+            &FeeVaultKeys {
+                owner: Pubkey::new_unique(),
+                token_mint: Pubkey::new_unique(),
+                token_vault: Pubkey::new_unique(),
+                ..Default::default()
+            },
+            vesting_duration,
+            0,
+            users,
+        )
+        .unwrap();
+    fee_vault
+}
@@ -0,0 +1,86 @@
+use crate::constants::MAX_PROTOCOL_FEE_BPS;
+use crate::error::FeeVaultError;
+use crate::state::{require_not_paused, Config, PauseFlag, ALL_PAUSE_FLAGS};
+use crate::ConfigParameters;
+use anchor_lang::prelude::*;
+
+#[test]
+fn test_get_protocol_fee() {
+    let mut config = Config::default();
+    config.initialize(&Pubkey::new_unique(), &Pubkey::new_unique(), 500);
+
+    assert_eq!(config.get_protocol_fee(1_000_000).unwrap(), 50_000);
+    // rounded down in favor of share holders
+    assert_eq!(config.get_protocol_fee(19).unwrap(), 0);
+    assert_eq!(config.get_protocol_fee(20).unwrap(), 1);
+    assert_eq!(config.get_protocol_fee(u64::MAX).unwrap(), u64::MAX / 20);
+
+    config.update(&Pubkey::new_unique(), 0);
+    assert_eq!(config.get_protocol_fee(1_000_000).unwrap(), 0);
+}
+
+#[test]
+fn test_validate_config_parameters() {
+    let params = ConfigParameters {
+        treasury: Pubkey::new_unique(),
+        protocol_fee_bps: MAX_PROTOCOL_FEE_BPS,
+    };
+    params.validate().unwrap();
+
+    let params = ConfigParameters {
+        treasury: Pubkey::new_unique(),
+        protocol_fee_bps: MAX_PROTOCOL_FEE_BPS + 1,
+    };
+    assert_eq!(
+        params.validate().unwrap_err(),
+        FeeVaultError::InvalidProtocolFeeBps.into()
+    );
+
+    let params = ConfigParameters {
+        treasury: Pubkey::default(),
+        protocol_fee_bps: 0,
+    };
+    assert_eq!(
+        params.validate().unwrap_err(),
+        FeeVaultError::InvalidTreasury.into()
+    );
+}
+
+#[test]
+fn test_pause_flags() {
+    let mut config = Config::default();
+    assert!(require_not_paused(Some(&config), PauseFlag::Fund).is_ok());
+    // uninitialized config never pauses
+    assert!(require_not_paused(None, PauseFlag::Claim).is_ok());
+
+    config.pause_flags = PauseFlag::Claim as u8;
+    assert!(config.is_paused(PauseFlag::Claim));
+    assert!(!config.is_paused(PauseFlag::Fund));
+    assert!(!config.is_paused(PauseFlag::FundByClaiming));
+    assert_eq!(
+        require_not_paused(Some(&config), PauseFlag::Claim).unwrap_err(),
+        FeeVaultError::Paused.into()
+    );
+
+    config.pause_flags = ALL_PAUSE_FLAGS;
+    for flag in [PauseFlag::Fund, PauseFlag::Claim, PauseFlag::FundByClaiming] {
+        assert!(config.is_paused(flag));
+    }
+}
+
+#[test]
+fn test_reject_unknown_pause_flags() {
+    let mut config = Config::default();
+    config.set_pause_flags(PauseFlag::Fund as u8).unwrap();
+
+    assert_eq!(
+        config.set_pause_flags(ALL_PAUSE_FLAGS + 1).unwrap_err(),
+        FeeVaultError::InvalidPauseFlags.into()
+    );
+    assert_eq!(
+        config.set_pause_flags(u8::MAX).unwrap_err(),
+        FeeVaultError::InvalidPauseFlags.into()
+    );
+    // flags are left unchanged on rejection
+    assert_eq!(config.pause_flags, PauseFlag::Fund as u8);
+}
@@ -0,0 +1,105 @@
+use crate::error::FeeVaultError;
+use crate::instructions::UserShare;
+use crate::tests::new_fee_vault;
+use anchor_lang::prelude::*;
+
+#[test]
+fn test_expired_share_keeps_fee_accrued_before_expiry() {
+    let expiring_user = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let mut fee_vault = new_fee_vault(
+        &[
+            UserShare {
+                address: expiring_user,
+                share: 100,
+                end_timestamp: 1_000,
+            },
+            UserShare {
+                address: user,
+                share: 100,
+                end_timestamp: 0,
+            },
+        ],
+        0,
+    );
+
+    fee_vault.fund_fee(1_000, 500).unwrap();
+    assert_eq!(fee_vault.total_share, 200);
+
+    // after expiry, newly funded fee only goes to the remaining share
+    let expired_flags = fee_vault.expired_flags();
+    fee_vault.fund_fee(1_000, 1_000).unwrap();
+    assert_eq!(fee_vault.total_share, 100);
+    assert_eq!(fee_vault.users[0].is_expired, 1);
+    assert_eq!(expired_flags[0], 0);
+    assert_eq!(fee_vault.users[0].pending_fee, 500);
+
+    let claimed = fee_vault
+        .validate_and_claim_fee(0, &expiring_user, 2_000)
+        .unwrap();
+    assert_eq!(claimed, 500);
+
+    let claimed = fee_vault.validate_and_claim_fee(1, &user, 2_000).unwrap();
+    assert_eq!(claimed, 1_500);
+
+    // nothing left for expired share after more funding
+    fee_vault.fund_fee(1_000, 3_000).unwrap();
+    let claimed = fee_vault
+        .validate_and_claim_fee(0, &expiring_user, 3_000)
+        .unwrap();
+    assert_eq!(claimed, 0);
+    assert_eq!(fee_vault.users[0].fee_claimed, 500);
+}
+
+#[test]
+fn test_claim_settles_expired_share_lazily() {
+    let expiring_user = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let mut fee_vault = new_fee_vault(
+        &[
+            UserShare {
+                address: expiring_user,
+                share: 100,
+                end_timestamp: 1_000,
+            },
+            UserShare {
+                address: user,
+                share: 300,
+                end_timestamp: 0,
+            },
+        ],
+        0,
+    );
+
+    fee_vault.fund_fee(4_000, 0).unwrap();
+
+    // no instruction touched the vault at expiry, claim settles it
+    let claimed = fee_vault
+        .validate_and_claim_fee(0, &expiring_user, 5_000)
+        .unwrap();
+    assert_eq!(claimed, 1_000);
+    assert_eq!(fee_vault.total_share, 300);
+    assert_eq!(fee_vault.users[0].pending_fee, 0);
+}
+
+#[test]
+fn test_fund_fee_without_active_share() {
+    let mut fee_vault = new_fee_vault(
+        &[
+            UserShare {
+                address: Pubkey::new_unique(),
+                share: 100,
+                end_timestamp: 1_000,
+            },
+            UserShare {
+                address: Pubkey::new_unique(),
+                share: 100,
+                end_timestamp: 2_000,
+            },
+        ],
+        0,
+    );
+
+    let err = fee_vault.fund_fee(1_000, 2_000).unwrap_err();
+    assert_eq!(err, FeeVaultError::NoActiveShare.into());
+}
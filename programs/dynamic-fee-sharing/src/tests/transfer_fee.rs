@@ -0,0 +1,80 @@
+use crate::instructions::{InitializeFeeVaultParameters, UserShare};
+use crate::state::{FeeVault, FeeVaultKeys, TransferFeeMode};
+use anchor_lang::prelude::*;
+
+fn new_users() -> Vec<UserShare> {
+    vec![
+        UserShare {
+            address: Pubkey::new_unique(),
+            share: 100,
+            end_timestamp: 0,
+        },
+        UserShare {
+            address: Pubkey::new_unique(),
+            share: 100,
+            end_timestamp: 0,
+        },
+    ]
+}
+
+#[test]
+fn test_validate_transfer_fee_mode() {
+    let mut params = InitializeFeeVaultParameters {
+        vesting_duration: 0,
+        duplicate_user_mode: 0,
+        transfer_fee_mode: TransferFeeMode::Carried.into(),
+        allow_permanent_delegate: 0,
+        padding_0: [0; 5],
+        padding: [0; 6],
+        users: new_users(),
+        metadata: None,
+    };
+    params.validate(0).unwrap();
+
+    params.transfer_fee_mode = 2;
+    assert!(params.validate(0).is_err());
+}
+
+#[test]
+fn test_carry_transfer_fee_from_reserve() {
+    let users = new_users();
+    let mut fee_vault = FeeVault::default();
+    fee_vault
+        .initialize(
+            &FeeVaultKeys {
+                owner: Pubkey::new_unique(),
+                token_mint: Pubkey::new_unique(),
+                token_vault: Pubkey::new_unique(),
+                token_flag: 1,
+                ..Default::default()
+            },
+            0,
+            TransferFeeMode::Carried.into(),
+            &users,
+        )
+        .unwrap();
+    assert!(fee_vault.is_transfer_fee_carried());
+
+    fee_vault.fund_fee(1_000, 0).unwrap();
+    fee_vault.reserve_transfer_fee(10).unwrap();
+    assert_eq!(fee_vault.total_funded_fee, 1_000);
+    assert_eq!(fee_vault.transfer_fee_reserve, 10);
+
+    let claimed_fee = fee_vault
+        .validate_and_claim_fee(0, &users[0].address, 0)
+        .unwrap();
+    assert_eq!(claimed_fee, 500);
+    assert_eq!(fee_vault.carry_transfer_fee(5).unwrap(), 5);
+    assert_eq!(fee_vault.transfer_fee_reserve, 5);
+
+    // transfer fee raised after funding, carried fee is capped by reserve
+    assert_eq!(fee_vault.carry_transfer_fee(8).unwrap(), 5);
+    assert_eq!(fee_vault.transfer_fee_reserve, 0);
+    assert_eq!(fee_vault.carry_transfer_fee(8).unwrap(), 0);
+}
+
+#[test]
+fn test_transfer_fee_deducted_by_default() {
+    let fee_vault = FeeVault::default();
+    assert!(!fee_vault.is_transfer_fee_carried());
+}
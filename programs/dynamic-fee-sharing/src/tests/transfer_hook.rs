@@ -0,0 +1,81 @@
+use crate::constants::MAX_TRANSFER_HOOK_PROGRAM;
+use crate::error::FeeVaultError;
+use crate::state::Config;
+use crate::utils::token::is_whitelisted_transfer_hook;
+use anchor_lang::prelude::*;
+
+#[test]
+fn test_set_transfer_hook_programs() {
+    let mut config = Config::default();
+    let hook_program = Pubkey::new_unique();
+    config.set_transfer_hook_programs(&[hook_program]).unwrap();
+    assert!(config.is_whitelisted_transfer_hook_program(&hook_program));
+    assert!(!config.is_whitelisted_transfer_hook_program(&Pubkey::new_unique()));
+    // empty slot must not whitelist default pubkey
+    assert!(!config.is_whitelisted_transfer_hook_program(&Pubkey::default()));
+
+    let programs: Vec<Pubkey> = (0..MAX_TRANSFER_HOOK_PROGRAM)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    config.set_transfer_hook_programs(&programs).unwrap();
+    assert!(!config.is_whitelisted_transfer_hook_program(&hook_program));
+    for program in programs.iter() {
+        assert!(config.is_whitelisted_transfer_hook_program(program));
+    }
+
+    config.set_transfer_hook_programs(&[]).unwrap();
+    assert_eq!(
+        config.transfer_hook_programs,
+        [Pubkey::default(); MAX_TRANSFER_HOOK_PROGRAM]
+    );
+}
+
+#[test]
+fn test_set_invalid_transfer_hook_programs() {
+    let mut config = Config::default();
+
+    let programs: Vec<Pubkey> = (0..MAX_TRANSFER_HOOK_PROGRAM + 1)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    assert_eq!(
+        config.set_transfer_hook_programs(&programs).unwrap_err(),
+        FeeVaultError::InvalidTransferHookPrograms.into()
+    );
+
+    assert_eq!(
+        config
+            .set_transfer_hook_programs(&[Pubkey::default()])
+            .unwrap_err(),
+        FeeVaultError::InvalidTransferHookPrograms.into()
+    );
+
+    let hook_program = Pubkey::new_unique();
+    assert_eq!(
+        config
+            .set_transfer_hook_programs(&[hook_program, hook_program])
+            .unwrap_err(),
+        FeeVaultError::InvalidTransferHookPrograms.into()
+    );
+}
+
+#[test]
+fn test_is_whitelisted_transfer_hook() {
+    let hook_program = Pubkey::new_unique();
+    let mut config = Config::default();
+    config.set_transfer_hook_programs(&[hook_program]).unwrap();
+
+    // mint without hook program is always allowed
+    assert!(is_whitelisted_transfer_hook(None, None));
+    assert!(is_whitelisted_transfer_hook(None, Some(&config)));
+
+    assert!(is_whitelisted_transfer_hook(
+        Some(hook_program),
+        Some(&config)
+    ));
+    assert!(!is_whitelisted_transfer_hook(
+        Some(Pubkey::new_unique()),
+        Some(&config)
+    ));
+    // hook is rejected until config is initialized
+    assert!(!is_whitelisted_transfer_hook(Some(hook_program), None));
+}
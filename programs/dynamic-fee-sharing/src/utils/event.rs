@@ -0,0 +1,57 @@
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::Event;
+
+use crate::const_pda;
+use crate::constants::MAX_USER;
+use crate::event::EvtExpireShare;
+use crate::state::FeeVault;
+
+/// Emit event through self cpi the same way as anchor emit_cpi!, but sign with the precomputed
+/// bump of event authority, so accounts struct doesn't need to derive it
+pub fn emit_event_cpi<E: Event>(event_authority: &AccountInfo, event: &E) -> Result<()> {
+    let event_data = event.data();
+    let mut data = Vec::with_capacity(EVENT_IX_TAG_LE.len() + event_data.len());
+    data.extend_from_slice(EVENT_IX_TAG_LE);
+    data.extend_from_slice(&event_data);
+
+    let ix = Instruction {
+        program_id: crate::ID,
+        accounts: vec![AccountMeta::new_readonly(
+            const_pda::event_authority::ID,
+            true,
+        )],
+        data,
+    };
+    invoke_signed(&ix, &[event_authority.clone()], &[event_authority_seeds!()])?;
+
+    Ok(())
+}
+
+/// Emit EvtExpireShare for every share that is expired now but not in expired_flags
+pub fn emit_expired_shares(
+    event_authority: &AccountInfo,
+    fee_vault_key: Pubkey,
+    fee_vault: &FeeVault,
+    expired_flags: [u8; MAX_USER],
+) -> Result<()> {
+    for (index, (user, was_expired)) in fee_vault.users.iter().zip(expired_flags).enumerate() {
+        if user.is_expired == 1 && was_expired == 0 {
+            emit_event_cpi(
+                event_authority,
+                &EvtExpireShare {
+                    fee_vault: fee_vault_key,
+                    user: user.address,
+                    index: index as u8,
+                    pending_fee: user.pending_fee,
+                    fee_per_share_checkpoint: user.fee_per_share_checkpoint,
+                    total_share: fee_vault.total_share,
+                },
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -1 +1,3 @@
+pub mod event;
+pub mod protocol_fee;
 pub mod token;
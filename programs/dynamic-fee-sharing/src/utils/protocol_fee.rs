@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::error::FeeVaultError;
+use crate::state::Config;
+use crate::utils::token::TokenVaultAccounts;
+
+/// Charge protocol fee on the amount funded into token vault, and return the protocol fee.
+/// Protocol fee is not charged until config is initialized.
+pub fn collect_protocol_fee<'c: 'info, 'info>(
+    config: Option<&Config>,
+    protocol_fee_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    token_vault_accounts: &TokenVaultAccounts<'_, 'c, 'info>,
+    funded_amount: u64,
+) -> Result<u64> {
+    let Some(config) = config else {
+        return Ok(0);
+    };
+    let protocol_fee = config.get_protocol_fee(funded_amount)?;
+    if protocol_fee == 0 {
+        return Ok(0);
+    }
+
+    let protocol_fee_token_account =
+        protocol_fee_token_account.ok_or(FeeVaultError::InvalidProtocolFeeTokenAccount)?;
+    require!(
+        protocol_fee_token_account.owner.eq(&config.treasury)
+            && protocol_fee_token_account
+                .mint
+                .eq(&token_vault_accounts.token_mint.key()),
+        FeeVaultError::InvalidProtocolFeeTokenAccount
+    );
+
+    token_vault_accounts.transfer_to(protocol_fee_token_account, protocol_fee)?;
+
+    Ok(protocol_fee)
+}
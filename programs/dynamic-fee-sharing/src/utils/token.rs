@@ -1,18 +1,24 @@
-use anchor_lang::{prelude::*, solana_program::program::invoke_signed};
+use anchor_lang::prelude::*;
 use anchor_spl::{
     token::Token,
     token_2022::spl_token_2022::{
         self,
         extension::{
-            self, transfer_fee::TransferFee, BaseStateWithExtensions, ExtensionType,
-            StateWithExtensions,
+            self,
+            default_account_state::DefaultAccountState,
+            permanent_delegate::PermanentDelegate,
+            transfer_fee::{TransferFee, TransferFeeAmount, MAX_FEE_BASIS_POINTS},
+            transfer_hook, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
         },
+        onchain::invoke_transfer_checked,
+        state::AccountState,
     },
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
 use crate::error::FeeVaultError;
+use crate::state::Config;
 #[derive(
     AnchorSerialize, AnchorDeserialize, Debug, PartialEq, Eq, IntoPrimitive, TryFromPrimitive,
 )]
@@ -22,9 +28,7 @@ pub enum TokenProgramFlags {
     TokenProgram2022,
 }
 
-pub fn get_token_program_flags<'a, 'info>(
-    token_mint: &'a InterfaceAccount<'info, Mint>,
-) -> TokenProgramFlags {
+pub fn get_token_program_flags(token_mint: &InterfaceAccount<'_, Mint>) -> TokenProgramFlags {
     let token_mint_ai = token_mint.to_account_info();
 
     if token_mint_ai.owner.eq(&anchor_spl::token::ID) {
@@ -34,7 +38,19 @@ pub fn get_token_program_flags<'a, 'info>(
     }
 }
 
-pub fn is_supported_mint(mint_account: &InterfaceAccount<Mint>) -> Result<bool> {
+/// Per vault policy of token 2022 mint extensions, decided at initialization
+pub struct MintExtensionPolicy<'a> {
+    pub config: Option<&'a Config>, // whitelist of transfer hook programs
+    pub allow_permanent_delegate: bool,
+}
+
+/// Token vault only deals with raw amount. Interest bearing mint only changes UI amount, so it
+/// doesn't affect accounting. Mints that can't be transferred out of token vault, or whose
+/// delegate could drain it without vault opting in, are rejected.
+pub fn is_supported_mint(
+    mint_account: &InterfaceAccount<Mint>,
+    policy: &MintExtensionPolicy,
+) -> Result<bool> {
     let mint_info = mint_account.to_account_info();
     if *mint_info.owner == Token::id() {
         return Ok(true);
@@ -42,35 +58,94 @@ pub fn is_supported_mint(mint_account: &InterfaceAccount<Mint>) -> Result<bool>
 
     let mint_data = mint_info.try_borrow_data()?;
     let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
-    let extensions = mint.get_extension_types()?;
+    // extension unknown to this program, such as ScaledUiAmount, fails to be parsed
+    let Ok(extensions) = mint.get_extension_types() else {
+        return Ok(false);
+    };
     for e in extensions {
-        if e != ExtensionType::TransferFeeConfig
-            && e != ExtensionType::MetadataPointer
-            && e != ExtensionType::TokenMetadata
-        {
+        let is_supported = match e {
+            ExtensionType::TransferFeeConfig
+            | ExtensionType::MetadataPointer
+            | ExtensionType::TokenMetadata
+            | ExtensionType::InterestBearingConfig
+            | ExtensionType::MintCloseAuthority => true,
+            // token vault would be frozen on creation
+            ExtensionType::DefaultAccountState => {
+                mint.get_extension::<DefaultAccountState>()?.state
+                    == AccountState::Initialized as u8
+            }
+            ExtensionType::PermanentDelegate => {
+                policy.allow_permanent_delegate
+                    || Option::<Pubkey>::from(mint.get_extension::<PermanentDelegate>()?.delegate)
+                        .is_none()
+            }
+            ExtensionType::TransferHook => {
+                is_whitelisted_transfer_hook(transfer_hook::get_program_id(&mint), policy.config)
+            }
+            _ => false,
+        };
+        if !is_supported {
             return Ok(false);
         }
     }
     Ok(true)
 }
 
+pub fn get_transfer_hook_program_id(token_mint: &InterfaceAccount<Mint>) -> Result<Option<Pubkey>> {
+    let token_mint_info = token_mint.to_account_info();
+    if *token_mint_info.owner == Token::id() {
+        return Ok(None);
+    }
+
+    let token_mint_data = token_mint_info.try_borrow_data()?;
+    let token_mint_unpacked =
+        StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&token_mint_data)?;
+    Ok(transfer_hook::get_program_id(&token_mint_unpacked))
+}
+
+/// Mint without hook program is always allowed, hook program must be whitelisted in config
+pub fn is_whitelisted_transfer_hook(
+    transfer_hook_program_id: Option<Pubkey>,
+    config: Option<&Config>,
+) -> bool {
+    match transfer_hook_program_id {
+        None => true,
+        Some(program_id) => {
+            config.is_some_and(|config| config.is_whitelisted_transfer_hook_program(&program_id))
+        }
+    }
+}
+
+/// Hook program can be changed by hook authority after fee vault is initialized, so it is
+/// checked again before funding. Claiming is not blocked to let share holders withdraw.
+pub fn require_whitelisted_transfer_hook(
+    token_mint: &InterfaceAccount<Mint>,
+    config: Option<&Config>,
+) -> Result<()> {
+    require!(
+        is_whitelisted_transfer_hook(get_transfer_hook_program_id(token_mint)?, config),
+        FeeVaultError::TransferHookProgramNotWhitelisted
+    );
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct TransferFeeExcludedAmount {
     pub amount: u64,
     pub transfer_fee: u64,
 }
 
-pub fn calculate_transfer_fee_excluded_amount<'info>(
-    token_mint: &InterfaceAccount<'info, Mint>,
+pub fn calculate_transfer_fee_excluded_amount(
+    token_mint: &InterfaceAccount<'_, Mint>,
     transfer_fee_included_amount: u64,
 ) -> Result<TransferFeeExcludedAmount> {
     if let Some(epoch_transfer_fee) = get_epoch_transfer_fee(token_mint)? {
         let transfer_fee = epoch_transfer_fee
             .calculate_fee(transfer_fee_included_amount)
-            .ok_or_else(|| FeeVaultError::MathOverflow)?;
+            .ok_or(FeeVaultError::MathOverflow)?;
         let transfer_fee_excluded_amount = transfer_fee_included_amount
             .checked_sub(transfer_fee)
-            .ok_or_else(|| FeeVaultError::MathOverflow)?;
+            .ok_or(FeeVaultError::MathOverflow)?;
         return Ok(TransferFeeExcludedAmount {
             amount: transfer_fee_excluded_amount,
             transfer_fee,
@@ -83,8 +158,50 @@ pub fn calculate_transfer_fee_excluded_amount<'info>(
     })
 }
 
-pub fn get_epoch_transfer_fee<'info>(
-    token_mint: &InterfaceAccount<'info, Mint>,
+#[derive(Debug)]
+pub struct TransferFeeIncludedAmount {
+    pub amount: u64,
+    pub transfer_fee: u64,
+}
+
+pub fn calculate_transfer_fee_included_amount(
+    token_mint: &InterfaceAccount<'_, Mint>,
+    transfer_fee_excluded_amount: u64,
+) -> Result<TransferFeeIncludedAmount> {
+    if transfer_fee_excluded_amount == 0 {
+        return Ok(TransferFeeIncludedAmount {
+            amount: 0,
+            transfer_fee: 0,
+        });
+    }
+
+    if let Some(epoch_transfer_fee) = get_epoch_transfer_fee(token_mint)? {
+        let transfer_fee: u64 =
+            if u16::from(epoch_transfer_fee.transfer_fee_basis_points) == MAX_FEE_BASIS_POINTS {
+                // inverse fee is undefined when all transferred amount is taken as fee
+                u64::from(epoch_transfer_fee.maximum_fee)
+            } else {
+                epoch_transfer_fee
+                    .calculate_inverse_fee(transfer_fee_excluded_amount)
+                    .ok_or(FeeVaultError::MathOverflow)?
+            };
+        let transfer_fee_included_amount = transfer_fee_excluded_amount
+            .checked_add(transfer_fee)
+            .ok_or(FeeVaultError::MathOverflow)?;
+        return Ok(TransferFeeIncludedAmount {
+            amount: transfer_fee_included_amount,
+            transfer_fee,
+        });
+    }
+
+    Ok(TransferFeeIncludedAmount {
+        amount: transfer_fee_excluded_amount,
+        transfer_fee: 0,
+    })
+}
+
+pub fn get_epoch_transfer_fee(
+    token_mint: &InterfaceAccount<'_, Mint>,
 ) -> Result<Option<TransferFee>> {
     let token_mint_info = token_mint.to_account_info();
     if *token_mint_info.owner == Token::id() {
@@ -98,43 +215,79 @@ pub fn get_epoch_transfer_fee<'info>(
         token_mint_unpacked.get_extension::<extension::transfer_fee::TransferFeeConfig>()
     {
         let epoch = Clock::get()?.epoch;
-        return Ok(Some(transfer_fee_config.get_epoch_fee(epoch).clone()));
+        return Ok(Some(*transfer_fee_config.get_epoch_fee(epoch)));
     }
 
     Ok(None)
 }
 
+/// Transfer fee withheld in a token 2022 account, which is not harvested yet
+pub fn get_withheld_transfer_fee(token_account: &AccountInfo) -> Result<u64> {
+    if *token_account.owner == Token::id() {
+        return Ok(0);
+    }
+
+    let token_account_data = token_account.try_borrow_data()?;
+    let token_account_unpacked =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&token_account_data)?;
+    if let Ok(transfer_fee_amount) = token_account_unpacked.get_extension::<TransferFeeAmount>() {
+        return Ok(transfer_fee_amount.withheld_amount.into());
+    }
+
+    Ok(0)
+}
+
+/// Extra account metas of transfer hook are resolved from `remaining_accounts`
 pub fn transfer_from_user<'a, 'c: 'info, 'info>(
     authority: &'a Signer<'info>,
     token_mint: &'a InterfaceAccount<'info, Mint>,
     token_owner_account: &'a InterfaceAccount<'info, TokenAccount>,
     destination_token_account: &'a InterfaceAccount<'info, TokenAccount>,
     token_program: &'a Interface<'info, TokenInterface>,
+    remaining_accounts: &'c [AccountInfo<'info>],
     amount: u64,
 ) -> Result<()> {
-    let destination_account = destination_token_account.to_account_info();
-
-    let instruction = spl_token_2022::instruction::transfer_checked(
+    invoke_transfer_checked(
         token_program.key,
-        &token_owner_account.key(),
-        &token_mint.key(),
-        destination_account.key,
-        authority.key,
-        &[],
+        token_owner_account.to_account_info(),
+        token_mint.to_account_info(),
+        destination_token_account.to_account_info(),
+        authority.to_account_info(),
+        remaining_accounts,
         amount,
         token_mint.decimals,
+        &[],
     )?;
 
-    let account_infos = vec![
-        token_owner_account.to_account_info(),
-        token_mint.to_account_info(),
-        destination_account.to_account_info(),
-        authority.to_account_info(),
-    ];
+    Ok(())
+}
 
-    invoke_signed(&instruction, &account_infos, &[])?;
+/// Token vault with the accounts needed to transfer out of it, signed by fee vault authority
+pub struct TokenVaultAccounts<'a, 'c: 'info, 'info> {
+    pub fee_vault_authority: AccountInfo<'info>,
+    pub token_mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_vault: &'a InterfaceAccount<'info, TokenAccount>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    /// Extra account metas of transfer hook
+    pub remaining_accounts: &'c [AccountInfo<'info>],
+}
 
-    Ok(())
+impl<'c: 'info, 'info> TokenVaultAccounts<'_, 'c, 'info> {
+    pub fn transfer_to(
+        &self,
+        token_owner_account: &InterfaceAccount<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        transfer_from_fee_vault(
+            self.fee_vault_authority.clone(),
+            self.token_mint,
+            self.token_vault,
+            token_owner_account,
+            self.token_program,
+            self.remaining_accounts,
+            amount,
+        )
+    }
 }
 
 pub fn transfer_from_fee_vault<'c: 'info, 'info>(
@@ -143,29 +296,56 @@ pub fn transfer_from_fee_vault<'c: 'info, 'info>(
     token_vault: &InterfaceAccount<'info, TokenAccount>,
     token_owner_account: &InterfaceAccount<'info, TokenAccount>,
     token_program: &Interface<'info, TokenInterface>,
+    remaining_accounts: &'c [AccountInfo<'info>],
     amount: u64,
 ) -> Result<()> {
     let signer_seeds = fee_vault_authority_seeds!();
 
-    let instruction = spl_token_2022::instruction::transfer_checked(
+    invoke_transfer_checked(
         token_program.key,
-        &token_vault.key(),
-        &token_mint.key(),
-        &token_owner_account.key(),
-        &pool_authority.key(),
-        &[],
+        token_vault.to_account_info(),
+        token_mint.to_account_info(),
+        token_owner_account.to_account_info(),
+        pool_authority,
+        remaining_accounts,
         amount,
         token_mint.decimals,
+        &[&signer_seeds[..]],
     )?;
 
-    let account_infos = vec![
-        token_vault.to_account_info(),
-        token_mint.to_account_info(),
-        token_owner_account.to_account_info(),
-        pool_authority.to_account_info(),
-    ];
+    Ok(())
+}
 
-    invoke_signed(&instruction, &account_infos, &[&signer_seeds[..]])?;
+/// Associated token account of fee vault receiving claimed token, swept into token vault with
+/// fee vault signature
+pub struct FeeVaultAtaAccounts<'a, 'c: 'info, 'info> {
+    pub fee_vault: AccountInfo<'info>,
+    pub token_mint: &'a InterfaceAccount<'info, Mint>,
+    pub fee_vault_ata: &'a InterfaceAccount<'info, TokenAccount>,
+    pub token_program: &'a AccountInfo<'info>,
+    /// Extra account metas of transfer hook
+    pub remaining_accounts: &'c [AccountInfo<'info>],
+}
 
-    Ok(())
+impl<'c: 'info, 'info> FeeVaultAtaAccounts<'_, 'c, 'info> {
+    pub fn transfer_to(
+        &self,
+        fee_vault_signer_seeds: &[&[u8]],
+        token_vault: &InterfaceAccount<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        invoke_transfer_checked(
+            self.token_program.key,
+            self.fee_vault_ata.to_account_info(),
+            self.token_mint.to_account_info(),
+            token_vault.to_account_info(),
+            self.fee_vault.clone(),
+            self.remaining_accounts,
+            amount,
+            self.token_mint.decimals,
+            &[fee_vault_signer_seeds],
+        )?;
+
+        Ok(())
+    }
 }
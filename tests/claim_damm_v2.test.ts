@@ -1,5 +1,10 @@
 import { LiteSVM } from "litesvm";
-import { PublicKey, Keypair, Transaction } from "@solana/web3.js";
+import {
+  PublicKey,
+  Keypair,
+  PACKET_DATA_SIZE,
+  Transaction,
+} from "@solana/web3.js";
 import {
   generateUsers,
   getTokenBalance,
@@ -10,15 +15,42 @@ import {
 import {
   createToken,
   getFeeVault,
+  getProgramErrorCodeHexString,
   mintToken,
+  U64_MAX,
 } from "./common";
-import { createDammV2Pool, dammV2Swap, initializeAndFundReward } from "./common/damm_v2";
-import { claimDammV2Fee, claimDammV2Reward, createFeeVaultPda } from "./common/dfs";
+import {
+  createDammV2Pool,
+  DAMM_V2_PROGRAM_ID,
+  dammV2Swap,
+  getDammV2PoolState,
+  getDammV2PositionState,
+  initializeAndFundReward,
+  setDammV2PoolPartner,
+} from "./common/damm_v2";
+import {
+  claimDammV2Fee,
+  claimDammV2PartnerFee,
+  claimDammV2Reward,
+  createFeeVaultPda,
+  fundByClaimingFee,
+  fundByClaimingFeeInstruction,
+  getDammV2ClaimPositionFeeAccounts,
+  getDammV2ClaimPositionFeePayload,
+  permanentLockPosition,
+} from "./common/dfs";
+import {
+  buildV0Transaction,
+  getFeeVaultLookupTableAddresses,
+  getLookupTableAccount,
+  setLookupTable,
+} from "./common/lookup_table";
 import { BN } from "bn.js";
 import { expect } from "chai";
 import {
   AuthorityType,
   createSetAuthorityInstruction,
+  getAssociatedTokenAddressSync,
   TOKEN_2022_PROGRAM_ID,
 } from "@solana/spl-token";
 
@@ -58,6 +90,133 @@ describe("Fund by claiming damm v2", () => {
     positionNftAccount = createDmmV2PoolRes.positionNftAccount;
   });
 
+  it("Fail to fund by claiming with short payload", async () => {
+    const { feeVault, tokenVault } = await createFeeVaultPda(
+      svm,
+      admin,
+      vaultOwner.publicKey,
+      tokenBMint,
+      {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
+        padding: [],
+        users: [
+          {
+            address: shareHolder.publicKey,
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+          {
+            address: PublicKey.unique(),
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+        ],
+        metadata: null,
+      }
+    );
+
+    await fundByClaimingFee(
+      svm,
+      shareHolder,
+      feeVault,
+      tokenVault,
+      [],
+      Buffer.from([1, 2, 3]),
+      DAMM_V2_PROGRAM_ID,
+      getProgramErrorCodeHexString("InvalidPayloadLength")
+    );
+  });
+
+  it("Fail to fund by claiming less than min claimed amount", async () => {
+    const { feeVault, tokenVault } = await createFeeVaultPda(
+      svm,
+      admin,
+      vaultOwner.publicKey,
+      tokenBMint,
+      {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
+        padding: [],
+        users: [
+          {
+            address: shareHolder.publicKey,
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+          {
+            address: PublicKey.unique(),
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+        ],
+        metadata: null,
+      }
+    );
+
+    const setAuthorityIx = createSetAuthorityInstruction(
+      positionNftAccount,
+      creator.publicKey,
+      AuthorityType.AccountOwner,
+      feeVault,
+      [],
+      TOKEN_2022_PROGRAM_ID
+    );
+    const assignOwnerTx = new Transaction().add(setAuthorityIx);
+    assignOwnerTx.recentBlockhash = svm.latestBlockhash();
+    assignOwnerTx.sign(creator);
+
+    sendTransactionOrExpectThrowError(svm, assignOwnerTx);
+
+    // swap damm v2
+    await dammV2Swap(svm, {
+      payer: creator,
+      pool: dammV2Pool,
+      inputTokenMint: tokenAMint,
+      outputTokenMint: tokenBMint,
+      amountIn: new BN(10000 * 10 ** 6),
+      minimumAmountOut: new BN(0),
+    });
+
+    await claimDammV2Fee(
+      svm,
+      shareHolder,
+      creator,
+      feeVault,
+      tokenVault,
+      dammV2Pool,
+      position,
+      positionNftAccount,
+      undefined,
+      U64_MAX,
+      getProgramErrorCodeHexString("ClaimedAmountBelowMinimum")
+    );
+
+    // pending fee is claimed once the minimum is met
+    const preTokenVaultBalance = getTokenBalance(svm, tokenVault);
+    await claimDammV2Fee(
+      svm,
+      shareHolder,
+      creator,
+      feeVault,
+      tokenVault,
+      dammV2Pool,
+      position,
+      positionNftAccount,
+      undefined,
+      new BN(1)
+    );
+    expect(
+      Number(getTokenBalance(svm, tokenVault).sub(preTokenVaultBalance))
+    ).gt(0);
+  });
+
   it("Fund by claim damm v2 position fee", async () => {
     const { feeVault, tokenVault } = await createFeeVaultPda(
       svm,
@@ -65,17 +224,25 @@ describe("Fund by claiming damm v2", () => {
       vaultOwner.publicKey,
       tokenBMint,
       {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
         padding: [],
         users: [
           {
             address: shareHolder.publicKey,
             share: 100,
+            endTimestamp: new BN(0),
           },
           {
             address: PublicKey.unique(),
             share: 100,
+            endTimestamp: new BN(0),
           },
         ],
+        metadata: null,
       }
     );
 
@@ -134,6 +301,246 @@ describe("Fund by claiming damm v2", () => {
   });
 
 
+  it("Fund by claim damm v2 position fee and swap token a into vault mint", async () => {
+    const { feeVault, tokenVault } = await createFeeVaultPda(
+      svm,
+      admin,
+      vaultOwner.publicKey,
+      tokenBMint,
+      {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
+        padding: [],
+        users: [
+          {
+            address: shareHolder.publicKey,
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+          {
+            address: PublicKey.unique(),
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+        ],
+        metadata: null,
+      }
+    );
+
+    const setAuthorityIx = createSetAuthorityInstruction(
+      positionNftAccount,
+      creator.publicKey,
+      AuthorityType.AccountOwner,
+      feeVault,
+      [],
+      TOKEN_2022_PROGRAM_ID
+    );
+    const assignOwnerTx = new Transaction().add(setAuthorityIx);
+    assignOwnerTx.recentBlockhash = svm.latestBlockhash();
+    assignOwnerTx.sign(creator);
+
+    sendTransactionOrExpectThrowError(svm, assignOwnerTx);
+
+    // swap both directions, so position accrues fee in both tokens
+    await dammV2Swap(svm, {
+      payer: creator,
+      pool: dammV2Pool,
+      inputTokenMint: tokenAMint,
+      outputTokenMint: tokenBMint,
+      amountIn: new BN(10000 * 10 ** 6),
+      minimumAmountOut: new BN(0),
+    });
+    await dammV2Swap(svm, {
+      payer: creator,
+      pool: dammV2Pool,
+      inputTokenMint: tokenBMint,
+      outputTokenMint: tokenAMint,
+      amountIn: new BN(10000 * 10 ** 6),
+      minimumAmountOut: new BN(0),
+    });
+
+    // minimum is checked against the combined amount funded after the swap
+    await claimDammV2Fee(
+      svm,
+      shareHolder,
+      creator,
+      feeVault,
+      tokenVault,
+      dammV2Pool,
+      position,
+      positionNftAccount,
+      new BN(0),
+      U64_MAX,
+      getProgramErrorCodeHexString("ClaimedAmountBelowMinimum")
+    );
+
+    const preTokenVaultBalance = getTokenBalance(svm, tokenVault);
+
+    await claimDammV2Fee(
+      svm,
+      shareHolder,
+      creator,
+      feeVault,
+      tokenVault,
+      dammV2Pool,
+      position,
+      positionNftAccount,
+      new BN(0),
+      new BN(1)
+    );
+
+    const postTokenVaultBalance = getTokenBalance(svm, tokenVault);
+    const vaultState = getFeeVault(svm, feeVault);
+
+    const feeVaultTokenAAccount = getAssociatedTokenAddressSync(
+      tokenAMint,
+      feeVault,
+      true
+    );
+    expect(getTokenBalance(svm, feeVaultTokenAAccount).toString()).eq("0");
+    expect(vaultState.totalFundedFee.toString()).eq(
+      postTokenVaultBalance.sub(preTokenVaultBalance).toString()
+    );
+  });
+
+  it("Fee vault owner permanently locks position held by fee vault", async () => {
+    const { feeVault } = await createFeeVaultPda(
+      svm,
+      admin,
+      vaultOwner.publicKey,
+      tokenBMint,
+      {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
+        padding: [],
+        users: [
+          {
+            address: shareHolder.publicKey,
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+          {
+            address: PublicKey.unique(),
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+        ],
+        metadata: null,
+      }
+    );
+
+    const setAuthorityIx = createSetAuthorityInstruction(
+      positionNftAccount,
+      creator.publicKey,
+      AuthorityType.AccountOwner,
+      feeVault,
+      [],
+      TOKEN_2022_PROGRAM_ID
+    );
+    const assignOwnerTx = new Transaction().add(setAuthorityIx);
+    assignOwnerTx.recentBlockhash = svm.latestBlockhash();
+    assignOwnerTx.sign(creator);
+
+    sendTransactionOrExpectThrowError(svm, assignOwnerTx);
+
+    const unlockedLiquidity = getDammV2PositionState(
+      svm,
+      position
+    ).unlockedLiquidity;
+
+    await permanentLockPosition(
+      svm,
+      vaultOwner,
+      feeVault,
+      dammV2Pool,
+      position,
+      positionNftAccount,
+      unlockedLiquidity
+    );
+
+    const positionState = getDammV2PositionState(svm, position);
+    expect(positionState.unlockedLiquidity.toString()).eq("0");
+    expect(positionState.permanentLockedLiquidity.toString()).eq(
+      unlockedLiquidity.toString()
+    );
+  });
+
+  it("Fund by claiming damm v2 partner fee", async () => {
+    const { feeVault, tokenVault } = await createFeeVaultPda(
+      svm,
+      admin,
+      vaultOwner.publicKey,
+      tokenBMint,
+      {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
+        padding: [],
+        users: [
+          {
+            address: shareHolder.publicKey,
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+          {
+            address: PublicKey.unique(),
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+        ],
+        metadata: null,
+      }
+    );
+
+    await setDammV2PoolPartner(svm, dammV2Pool, feeVault, 50);
+
+    // swap damm v2, partner fee is charged in token b
+    await dammV2Swap(svm, {
+      payer: creator,
+      pool: dammV2Pool,
+      inputTokenMint: tokenAMint,
+      outputTokenMint: tokenBMint,
+      amountIn: new BN(10000 * 10 ** 6),
+      minimumAmountOut: new BN(0),
+    });
+
+    let vaultState = getFeeVault(svm, feeVault);
+
+    const preTotalFundedFee = vaultState.totalFundedFee;
+    const preFeePerShare = vaultState.feePerShare;
+
+    const preTokenVaultBalance = getTokenBalance(svm, tokenVault);
+
+    await claimDammV2PartnerFee(
+      svm,
+      shareHolder,
+      feeVault,
+      tokenVault,
+      dammV2Pool
+    );
+
+    const postTokenVaultBalance = getTokenBalance(svm, tokenVault);
+    vaultState = getFeeVault(svm, feeVault);
+
+    const postTotalFundedFee = vaultState.totalFundedFee;
+    const postFeePerShare = vaultState.feePerShare;
+
+    expect(Number(postTokenVaultBalance.sub(preTokenVaultBalance))).gt(0);
+    expect(postTotalFundedFee.sub(preTotalFundedFee).toString()).eq(
+      postTokenVaultBalance.sub(preTokenVaultBalance).toString()
+    );
+    expect(Number(postFeePerShare.sub(preFeePerShare))).gt(0);
+    expect(getDammV2PoolState(svm, dammV2Pool).partnerBFee.toString()).eq("0");
+  });
+
   it("Fund by claiming damm v2 reward", async () => {
     const { feeVault, tokenVault } = await createFeeVaultPda(
       svm,
@@ -141,17 +548,25 @@ describe("Fund by claiming damm v2", () => {
       vaultOwner.publicKey,
       rewardMint,
       {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
         padding: [],
         users: [
           {
             address: shareHolder.publicKey,
             share: 100,
+            endTimestamp: new BN(0),
           },
           {
             address: PublicKey.unique(),
             share: 100,
+            endTimestamp: new BN(0),
           },
         ],
+        metadata: null,
       }
     );
 
@@ -204,4 +619,144 @@ describe("Fund by claiming damm v2", () => {
     );
     expect(Number(postFeePerShare.sub(preFeePerShare))).gt(0);
   });
+
+  it("Batch fund by claiming damm v2 position fee in v0 transaction with lookup table", async () => {
+    // second pool of another token a, so that each fee vault holds its own position
+    const otherTokenAMint = createToken(svm, admin, admin.publicKey, null);
+    mintToken(svm, admin, otherTokenAMint, admin, creator.publicKey);
+    const otherPoolRes = await createDammV2Pool(
+      svm,
+      creator,
+      otherTokenAMint,
+      tokenBMint
+    );
+
+    const claims = [
+      {
+        pool: dammV2Pool,
+        tokenAMint,
+        position,
+        positionNftAccount,
+      },
+      {
+        pool: otherPoolRes.pool,
+        tokenAMint: otherTokenAMint,
+        position: otherPoolRes.position,
+        positionNftAccount: otherPoolRes.positionNftAccount,
+      },
+    ];
+
+    const instructions = [];
+    const lookupTableAddresses = [];
+    const tokenVaults = [];
+    for (const claim of claims) {
+      const { feeVault, tokenVault } = await createFeeVaultPda(
+        svm,
+        admin,
+        vaultOwner.publicKey,
+        tokenBMint,
+        {
+          vestingDuration: new BN(0),
+          duplicateUserMode: 0,
+          transferFeeMode: 0,
+          allowPermanentDelegate: 0,
+          padding0: [],
+          padding: [],
+          users: [
+            {
+              address: shareHolder.publicKey,
+              share: 100,
+              endTimestamp: new BN(0),
+            },
+            {
+              address: PublicKey.unique(),
+              share: 100,
+              endTimestamp: new BN(0),
+            },
+          ],
+          metadata: null,
+        }
+      );
+      tokenVaults.push(tokenVault);
+
+      const setAuthorityIx = createSetAuthorityInstruction(
+        claim.positionNftAccount,
+        creator.publicKey,
+        AuthorityType.AccountOwner,
+        feeVault,
+        [],
+        TOKEN_2022_PROGRAM_ID
+      );
+      const assignOwnerTx = new Transaction().add(setAuthorityIx);
+      assignOwnerTx.recentBlockhash = svm.latestBlockhash();
+      assignOwnerTx.sign(creator);
+
+      sendTransactionOrExpectThrowError(svm, assignOwnerTx);
+
+      await dammV2Swap(svm, {
+        payer: creator,
+        pool: claim.pool,
+        inputTokenMint: claim.tokenAMint,
+        outputTokenMint: tokenBMint,
+        amountIn: new BN(10000 * 10 ** 6),
+        minimumAmountOut: new BN(0),
+      });
+
+      const remainingAccounts = getDammV2ClaimPositionFeeAccounts(
+        svm,
+        feeVault,
+        tokenVault,
+        claim.pool,
+        claim.position,
+        claim.positionNftAccount,
+        getAssociatedTokenAddressSync(claim.tokenAMint, creator.publicKey, true)
+      );
+
+      instructions.push(
+        await fundByClaimingFeeInstruction(
+          svm,
+          shareHolder.publicKey,
+          feeVault,
+          tokenVault,
+          remainingAccounts,
+          getDammV2ClaimPositionFeePayload(),
+          DAMM_V2_PROGRAM_ID
+        )
+      );
+      lookupTableAddresses.push(
+        ...getFeeVaultLookupTableAddresses(
+          feeVault,
+          tokenVault,
+          DAMM_V2_PROGRAM_ID,
+          remainingAccounts
+        )
+      );
+    }
+
+    const lookupTable = setLookupTable(
+      svm,
+      vaultOwner.publicKey,
+      lookupTableAddresses.filter(
+        (address, i) =>
+          lookupTableAddresses.findIndex((a) => a.equals(address)) === i
+      )
+    );
+
+    const preTokenVaultBalances = tokenVaults.map((tokenVault) =>
+      getTokenBalance(svm, tokenVault)
+    );
+
+    const tx = buildV0Transaction(svm, shareHolder, instructions, [
+      getLookupTableAccount(svm, lookupTable),
+    ]);
+    expect(tx.serialize().length).lte(PACKET_DATA_SIZE);
+
+    sendTransactionOrExpectThrowError(svm, tx);
+
+    tokenVaults.forEach((tokenVault, i) => {
+      expect(
+        Number(getTokenBalance(svm, tokenVault).sub(preTokenVaultBalances[i]))
+      ).gt(0);
+    });
+  });
 });
@@ -1,10 +1,12 @@
 import { BN } from "bn.js";
 import { Keypair, PublicKey } from "@solana/web3.js";
+import { TOKEN_2022_PROGRAM_ID } from "@solana/spl-token";
 import { expect } from "chai";
 import { LiteSVM } from "litesvm";
 import { generateUsers, getTokenBalance, startSvm } from "./common/svm";
 import {
   createToken,
+  deriveFeeVaultPdaAddress,
   getFeeVault,
   mintToken,
 } from "./common";
@@ -15,6 +17,7 @@ import {
   createVirtualPool,
   getVirtualConfigState,
   getVirtualPoolState,
+  setVirtualPoolMigratedWithLeftover,
   swap,
   SwapParams,
   transferCreator,
@@ -24,6 +27,7 @@ import {
   claimDbcPartnerTradingFee,
   createFeeVaultPda,
   withdrawDbcCreatorSurplus,
+  withdrawDbcLeftover,
   withdrawDbcPartnerSurplus,
   withdrawMigrationFee,
 } from "./common/dfs";
@@ -55,17 +59,25 @@ describe("Funding by claiming in DBC", () => {
       vaultOwner.publicKey,
       quoteMint,
       {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
         padding: [],
         users: [
           {
             address: shareHolder.publicKey,
             share: 100,
+            endTimestamp: new BN(0),
           },
           {
             address: PublicKey.unique(),
             share: 100,
+            endTimestamp: new BN(0),
           },
         ],
+        metadata: null,
       }
     );
 
@@ -114,17 +126,25 @@ describe("Funding by claiming in DBC", () => {
       vaultOwner.publicKey,
       quoteMint,
       {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
         padding: [],
         users: [
           {
             address: shareHolder.publicKey,
             share: 100,
+            endTimestamp: new BN(0),
           },
           {
             address: PublicKey.unique(),
             share: 100,
+            endTimestamp: new BN(0),
           },
         ],
+        metadata: null,
       }
     );
 
@@ -174,17 +194,25 @@ describe("Funding by claiming in DBC", () => {
       vaultOwner.publicKey,
       quoteMint,
       {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
         padding: [],
         users: [
           {
             address: shareHolder.publicKey,
             share: 100,
+            endTimestamp: new BN(0),
           },
           {
             address: PublicKey.unique(),
             share: 100,
+            endTimestamp: new BN(0),
           },
         ],
+        metadata: null,
       }
     );
 
@@ -233,17 +261,25 @@ describe("Funding by claiming in DBC", () => {
       vaultOwner.publicKey,
       quoteMint,
       {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
         padding: [],
         users: [
           {
             address: shareHolder.publicKey,
             share: 100,
+            endTimestamp: new BN(0),
           },
           {
             address: PublicKey.unique(),
             share: 100,
+            endTimestamp: new BN(0),
           },
         ],
+        metadata: null,
       }
     );
 
@@ -292,17 +328,25 @@ describe("Funding by claiming in DBC", () => {
       vaultOwner.publicKey,
       quoteMint,
       {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
         padding: [],
         users: [
           {
             address: shareHolder.publicKey,
             share: 100,
+            endTimestamp: new BN(0),
           },
           {
             address: PublicKey.unique(),
             share: 100,
+            endTimestamp: new BN(0),
           },
         ],
+        metadata: null,
       }
     );
 
@@ -344,6 +388,88 @@ describe("Funding by claiming in DBC", () => {
     );
     expect(Number(postFeePerShare.sub(preFeePerShare))).gt(0);
   });
+  it("withdraw dbc leftover", async () => {
+    // fee vault of base mint receives leftover, so both addresses are known before config
+    const baseMint = Keypair.generate();
+    const baseKp = Keypair.generate();
+    const feeVaultAddress = deriveFeeVaultPdaAddress(
+      baseKp.publicKey,
+      baseMint.publicKey
+    );
+
+    const { virtualPool, virtualPoolConfig } = await setupPool(
+      svm,
+      admin,
+      user,
+      poolCreator,
+      payer,
+      feeVaultAddress,
+      quoteMint,
+      baseMint
+    );
+    await setVirtualPoolMigratedWithLeftover(svm, virtualPool);
+
+    const { feeVault, tokenVault } = await createFeeVaultPda(
+      svm,
+      admin,
+      vaultOwner.publicKey,
+      baseMint.publicKey,
+      {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
+        padding: [],
+        users: [
+          {
+            address: shareHolder.publicKey,
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+          {
+            address: PublicKey.unique(),
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+        ],
+        metadata: null,
+      },
+      TOKEN_2022_PROGRAM_ID,
+      baseKp
+    );
+    expect(feeVault.toBase58()).eq(feeVaultAddress.toBase58());
+
+    let vaultState = getFeeVault(svm, feeVault);
+
+    const preTotalFundedFee = vaultState.totalFundedFee;
+    const preFeePerShare = vaultState.feePerShare;
+
+    const preTokenVaultBalance = getTokenBalance(svm, tokenVault);
+
+    await withdrawDbcLeftover(
+      svm,
+      shareHolder,
+      feeVault,
+      tokenVault,
+      virtualPoolConfig,
+      virtualPool
+    );
+
+    const postTokenVaultBalance = getTokenBalance(svm, tokenVault);
+    vaultState = getFeeVault(svm, feeVault);
+
+    const postTotalFundedFee = vaultState.totalFundedFee;
+    const postFeePerShare = vaultState.feePerShare;
+
+    // leftover is swept from ATA of fee vault into token vault
+    expect(Number(postTokenVaultBalance.sub(preTokenVaultBalance))).gt(0);
+    expect(postTotalFundedFee.sub(preTotalFundedFee).toString()).eq(
+      postTokenVaultBalance.sub(preTokenVaultBalance).toString()
+    );
+    expect(Number(postFeePerShare.sub(preFeePerShare))).gt(0);
+    expect(getVirtualPoolState(svm, virtualPool).isWithdrawLeftover).eq(1);
+  });
 });
 
 async function setupPool(
@@ -353,7 +479,8 @@ async function setupPool(
   poolCreator: Keypair,
   payer: Keypair,
   feeVault: PublicKey,
-  quoteMint: PublicKey
+  quoteMint: PublicKey,
+  baseMint?: Keypair
 ) {
   let instructionParams = buildDefaultCurve();
   const params: CreateConfigParams = {
@@ -379,6 +506,7 @@ async function setupPool(
     poolCreator: poolCreator,
     quoteMint,
     config: virtualPoolConfig,
+    baseMint,
     instructionParams: {
       name: "test token spl",
       symbol: "TEST",
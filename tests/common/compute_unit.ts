@@ -0,0 +1,99 @@
+import fs from "fs";
+import { FailedTransactionMetadata, TransactionMetadata } from "litesvm";
+
+// CU_BASELINE_PATH stores compute units of another build aside, eg. before and after a change
+const BASELINE_PATH =
+  process.env.CU_BASELINE_PATH ?? "./tests/fixtures/compute_unit_baseline.json";
+
+// whitelisted actions of fund_by_claiming_fee, each must be benchmarked by the DAMM v2 or DBC tests
+const WHITELISTED_ACTIONS = [
+  "damm_v2/claim_position_fee",
+  "damm_v2/claim_reward",
+  "damm_v2/claim_partner_fee",
+  "dbc/creator_withdraw_surplus",
+  "dbc/claim_creator_trading_fee",
+  "dbc/partner_withdraw_surplus",
+  "dbc/claim_trading_fee",
+  "dbc/withdraw_migration_fee",
+  "dbc/withdraw_leftover",
+];
+
+// highest compute units consumed by each benchmarked case in this run
+const measured = new Map<string, number>();
+
+export function recordComputeUnits(
+  name: string,
+  result: TransactionMetadata | FailedTransactionMetadata
+) {
+  if (!(result instanceof TransactionMetadata)) {
+    return;
+  }
+  const computeUnits = Number(result.computeUnitsConsumed());
+  measured.set(name, Math.max(measured.get(name) ?? 0, computeUnits));
+}
+
+// Compare compute units measured in this run against the stored baseline, and fail when any
+// case consumes more or has no stored baseline. Run with UPDATE_CU_BASELINE=1 to store measured
+// compute units instead
+export function checkComputeUnitBaseline() {
+  const uncovered = WHITELISTED_ACTIONS.filter(
+    (action) =>
+      !Array.from(measured.keys()).some((name) =>
+        name.startsWith(`fund_by_claiming_fee/${action}/`)
+      )
+  );
+  if (uncovered.length > 0) {
+    throw new Error(
+      `No compute units measured for fund_by_claiming_fee of ${uncovered.join(
+        ", "
+      )}`
+    );
+  }
+
+  const baseline: Record<string, number> = fs.existsSync(BASELINE_PATH)
+    ? JSON.parse(fs.readFileSync(BASELINE_PATH, "utf8"))
+    : {};
+
+  const rows = Array.from(measured.keys())
+    .sort()
+    .map((name) => {
+      const computeUnits = measured.get(name);
+      const stored = baseline[name];
+      return {
+        name,
+        baseline: stored,
+        computeUnits,
+        delta: stored === undefined ? undefined : computeUnits - stored,
+      };
+    });
+  console.table(rows);
+
+  if (process.env.UPDATE_CU_BASELINE) {
+    const updated: Record<string, number> = { ...baseline };
+    rows.forEach((row) => (updated[row.name] = row.computeUnits));
+    const sorted: Record<string, number> = {};
+    Object.keys(updated)
+      .sort()
+      .forEach((name) => (sorted[name] = updated[name]));
+    fs.writeFileSync(BASELINE_PATH, JSON.stringify(sorted, null, 2) + "\n");
+    return;
+  }
+
+  const missing = rows.filter((row) => row.baseline === undefined);
+  if (missing.length > 0) {
+    throw new Error(
+      `No compute unit baseline for ${missing
+        .map((row) => row.name)
+        .join(", ")}, run with UPDATE_CU_BASELINE=1 to store it`
+    );
+  }
+
+  const exceeded = rows.filter((row) => row.delta > 0);
+  if (exceeded.length > 0) {
+    throw new Error(
+      `Compute units grew past baseline: ${exceeded
+        .map((row) => `${row.name} ${row.baseline} -> ${row.computeUnits}`)
+        .join(", ")}`
+    );
+  }
+}
@@ -170,6 +170,31 @@ export function getDammV2PoolState(svm: LiteSVM, dammV2Pool: PublicKey): Pool {
   return program.coder.accounts.decode("pool", Buffer.from(account.data));
 }
 
+// pool partner is only set by config created by DAMM v2 admin, so it is mocked in tests
+export async function setDammV2PoolPartner(
+  svm: LiteSVM,
+  dammV2Pool: PublicKey,
+  partner: PublicKey,
+  partnerFeePercent: number
+) {
+  const program = createDammV2Program();
+  const poolState = getDammV2PoolState(svm, dammV2Pool);
+  poolState.partner = partner;
+  poolState.poolFees.partnerFeePercent = partnerFeePercent;
+
+  const account = svm.getAccount(dammV2Pool);
+  svm.setAccount(dammV2Pool, {
+    ...account,
+    data: await program.coder.accounts.encode("pool", poolState),
+  });
+}
+
+export function getDammV2PositionState(svm: LiteSVM, position: PublicKey): Position {
+  const program = createDammV2Program();
+  const account = svm.getAccount(position);
+  return program.coder.accounts.decode("position", Buffer.from(account.data));
+}
+
 export function deriveDammV2PositionAddress(positionNft: PublicKey): PublicKey {
   return PublicKey.findProgramAddressSync(
     [Buffer.from("position"), positionNft.toBuffer()],
@@ -86,6 +86,7 @@ export type CreatePoolToken2022Params = {
   poolCreator: Keypair;
   quoteMint: PublicKey;
   config: PublicKey;
+  baseMint?: Keypair;
   instructionParams: {
     name: string;
     symbol: string;
@@ -100,7 +101,7 @@ export async function createVirtualPool(
   const { payer, quoteMint, config, instructionParams, poolCreator } = params;
   const program = createDBCProgram();
   const poolAuthority = deriveDbcPoolAuthority();
-  const baseMintKP = Keypair.generate();
+  const baseMintKP = params.baseMint ?? Keypair.generate();
   const pool = derivePoolAddress(config, baseMintKP.publicKey, quoteMint);
   const baseVault = deriveTokenVaultAddress(baseMintKP.publicKey, pool);
   const quoteVault = deriveTokenVaultAddress(quoteMint, pool);
@@ -303,6 +304,38 @@ export function getVirtualConfigState(
   return program.coder.accounts.decode("poolConfig", Buffer.from(account.data));
 }
 
+// migration is not run in tests, so a completed pool is mocked as migrated, with config of fixed
+// token supply to leave base token in base vault for leftover receiver
+export async function setVirtualPoolMigratedWithLeftover(
+  svm: LiteSVM,
+  virtualPool: PublicKey
+) {
+  const program = createDBCProgram();
+  const virtualPoolState = getVirtualPoolState(svm, virtualPool);
+  virtualPoolState.isMigrated = 1;
+  virtualPoolState.migrationProgress = 3; // CreatedPool
+  const poolAccount = svm.getAccount(virtualPool);
+  svm.setAccount(virtualPool, {
+    ...poolAccount,
+    data: await program.coder.accounts.encode("virtualPool", virtualPoolState),
+  });
+
+  const configState = getVirtualConfigState(svm, virtualPoolState.config);
+  const baseMintAccount = svm.getAccount(virtualPoolState.baseMint);
+  const baseSupply = new BN(
+    Buffer.from(baseMintAccount.data).subarray(36, 44),
+    "le"
+  );
+  configState.fixedTokenSupplyFlag = 1;
+  configState.preMigrationTokenSupply = baseSupply;
+  configState.postMigrationTokenSupply = baseSupply;
+  const configAccount = svm.getAccount(virtualPoolState.config);
+  svm.setAccount(virtualPoolState.config, {
+    ...configAccount,
+    data: await program.coder.accounts.encode("poolConfig", configState),
+  });
+}
+
 export function deriveMigrationMetadataAddress(
   virtual_pool: PublicKey
 ): PublicKey {
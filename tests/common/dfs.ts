@@ -1,4 +1,11 @@
-import { AccountMeta, Keypair, PublicKey } from "@solana/web3.js";
+import {
+  AccountMeta,
+  Keypair,
+  PublicKey,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { BN } from "bn.js";
 import CpAmmIDL from "../../idls/damm_v2.json";
 import DynamicBondingCurveIDL from "../../idls/dynamic_bonding_curve.json";
 import {
@@ -6,6 +13,7 @@ import {
   deriveFeeVaultAuthorityAddress,
   deriveFeeVaultPdaAddress,
   deriveTokenVaultAddress,
+  getFeeVault,
   getOrCreateAtA,
   InitializeFeeVaultParameters,
   U64_MAX,
@@ -24,6 +32,7 @@ import {
   getProgramFromFlagDammV2,
 } from "./damm_v2";
 import { sendTransactionOrExpectThrowError } from "./svm";
+import { recordComputeUnits } from "./compute_unit";
 import {
   DBC_PROGRAM_ID,
   deriveDbcEventAuthority,
@@ -37,13 +46,14 @@ export async function createFeeVaultPda(
   admin: Keypair,
   vaultOwner: PublicKey,
   tokenMint: PublicKey,
-  params: InitializeFeeVaultParameters
+  params: InitializeFeeVaultParameters,
+  tokenProgram = TOKEN_PROGRAM_ID,
+  baseKp = Keypair.generate()
 ): Promise<{
   feeVault: PublicKey;
   tokenVault: PublicKey;
 }> {
   const program = createProgram();
-  const baseKp = Keypair.generate();
   const feeVault = deriveFeeVaultPdaAddress(baseKp.publicKey, tokenMint);
   const tokenVault = deriveTokenVaultAddress(feeVault);
   const feeVaultAuthority = deriveFeeVaultAuthorityAddress();
@@ -57,7 +67,7 @@ export async function createFeeVaultPda(
       tokenMint,
       owner: vaultOwner,
       payer: admin.publicKey,
-      tokenProgram: TOKEN_PROGRAM_ID,
+      tokenProgram,
     })
     .transaction();
 
@@ -69,16 +79,81 @@ export async function createFeeVaultPda(
   return { feeVault, tokenVault };
 }
 
-async function fundByClaimingFee(svm: LiteSVM, signer: Keypair, feeVault: PublicKey, tokenVault: PublicKey, remainingAccounts: AccountMeta[], payload: Buffer, sourceProgram: PublicKey) {
+// benchmark name of a whitelisted action, e.g. damm_v2/claim_position_fee/spl_token
+function whitelistedActionName(
+  svm: LiteSVM,
+  sourceProgram: PublicKey,
+  payload: Buffer,
+  tokenMint: PublicKey
+): string {
+  const isDammV2 = sourceProgram.equals(DAMM_V2_PROGRAM_ID);
+  const idl = isDammV2 ? CpAmmIDL : DynamicBondingCurveIDL;
+  const instruction = idl.instructions.find((instruction) =>
+    Buffer.from(instruction.discriminator).equals(payload.subarray(0, 8))
+  );
+  const tokenProgram = svm.getAccount(tokenMint).owner.equals(TOKEN_2022_PROGRAM_ID)
+    ? "token_2022"
+    : "spl_token";
+  return `${isDammV2 ? "damm_v2" : "dbc"}/${instruction.name}/${tokenProgram}`;
+}
+
+export async function fundByClaimingFeeInstruction(svm: LiteSVM, signer: PublicKey, feeVault: PublicKey, tokenVault: PublicKey, remainingAccounts: AccountMeta[], payload: Buffer, sourceProgram: PublicKey, minClaimedAmount: BN | null = null): Promise<TransactionInstruction> {
   const program = createProgram();
+  const tokenMint = getFeeVault(svm, feeVault).tokenMint;
+
+  const methods = minClaimedAmount
+    ? program.methods.fundByClaimingFeeWithMinimum(payload, minClaimedAmount)
+    : program.methods.fundByClaimingFee(payload);
+
+  return methods
+    .accountsPartial({
+      feeVault,
+      tokenVault,
+      signer,
+      sourceProgram,
+      tokenMint,
+      tokenProgram: svm.getAccount(tokenMint).owner,
+      protocolFeeTokenAccount: null,
+    })
+    .remainingAccounts(
+      remainingAccounts
+    )
+    .instruction();
+}
+
+export async function fundByClaimingFee(svm: LiteSVM, signer: Keypair, feeVault: PublicKey, tokenVault: PublicKey, remainingAccounts: AccountMeta[], payload: Buffer, sourceProgram: PublicKey, errorCode?: number, minClaimedAmount: BN | null = null) {
+  const tokenMint = getFeeVault(svm, feeVault).tokenMint;
+
+  const tx = new Transaction().add(
+    await fundByClaimingFeeInstruction(svm, signer.publicKey, feeVault, tokenVault, remainingAccounts, payload, sourceProgram, minClaimedAmount)
+  );
+
+  tx.recentBlockhash = svm.latestBlockhash();
+  tx.sign(signer);
+
+  const result = sendTransactionOrExpectThrowError(svm, tx, false, errorCode);
+  recordComputeUnits(
+    `${minClaimedAmount ? "fund_by_claiming_fee_with_minimum" : "fund_by_claiming_fee"}/${whitelistedActionName(svm, sourceProgram, payload, tokenMint)}`,
+    result
+  );
+
+  return result
+}
+
+export async function fundByClaimingFeeAndSwap(svm: LiteSVM, signer: Keypair, feeVault: PublicKey, tokenVault: PublicKey, remainingAccounts: AccountMeta[], payload: Buffer, sourceProgram: PublicKey, minimumAmountOut: BN, minClaimedAmount: BN = new BN(0), errorCode?: number) {
+  const program = createProgram();
+  const tokenMint = getFeeVault(svm, feeVault).tokenMint;
 
   const tx = await program.methods
-    .fundByClaimingFee(payload)
+    .fundByClaimingFeeAndSwap(payload, minimumAmountOut, minClaimedAmount)
     .accountsPartial({
       feeVault,
       tokenVault,
       signer: signer.publicKey,
-      sourceProgram
+      sourceProgram,
+      tokenMint,
+      tokenProgram: svm.getAccount(tokenMint).owner,
+      protocolFeeTokenAccount: null,
     })
     .remainingAccounts(
       remainingAccounts
@@ -88,32 +163,32 @@ async function fundByClaimingFee(svm: LiteSVM, signer: Keypair, feeVault: Public
   tx.recentBlockhash = svm.latestBlockhash();
   tx.sign(signer);
 
-  const result = sendTransactionOrExpectThrowError(svm, tx);
+  const result = sendTransactionOrExpectThrowError(svm, tx, false, errorCode);
+  recordComputeUnits(
+    `fund_by_claiming_fee_and_swap/${whitelistedActionName(svm, sourceProgram, payload, tokenMint)}`,
+    result
+  );
 
   return result
 }
 
-export async function claimDammV2Fee(
+export function getDammV2ClaimPositionFeePayload(): Buffer {
+  const claimPositionFeeDisc = CpAmmIDL.instructions.find(instruction => instruction.name === "claim_position_fee").discriminator;
+  return Buffer.from(claimPositionFeeDisc)
+}
+
+export function getDammV2ClaimPositionFeeAccounts(
   svm: LiteSVM,
-  signer: Keypair,
-  owner: Keypair,
   feeVault: PublicKey,
   tokenVault: PublicKey,
   dammv2Pool: PublicKey,
   position: PublicKey,
   positionNftAccount: PublicKey,
-) {
-
+  tokenAAccount: PublicKey,
+): AccountMeta[] {
   const dammV2PoolState = getDammV2PoolState(svm, dammv2Pool);
 
-  const tokenAAccount = getAssociatedTokenAddressSync(
-    dammV2PoolState.tokenAMint,
-    owner.publicKey,
-    true,
-    getProgramFromFlagDammV2(dammV2PoolState.tokenAFlag)
-  );
-
-  const remainingAccounts = [
+  return [
     {
       isSigner: false,
       isWritable: false,
@@ -191,11 +266,57 @@ export async function claimDammV2Fee(
       pubkey: DAMM_V2_PROGRAM_ID,
     },
   ];
+}
 
-  const claimPositionFeeDisc = CpAmmIDL.instructions.find(instruction => instruction.name === "claim_position_fee").discriminator;
-  const payload = Buffer.from(claimPositionFeeDisc)
+export async function claimDammV2Fee(
+  svm: LiteSVM,
+  signer: Keypair,
+  owner: Keypair,
+  feeVault: PublicKey,
+  tokenVault: PublicKey,
+  dammv2Pool: PublicKey,
+  position: PublicKey,
+  positionNftAccount: PublicKey,
+  minimumAmountOut?: BN,
+  minClaimedAmount?: BN,
+  errorCode?: number,
+) {
 
-  await fundByClaimingFee(svm, signer, feeVault, tokenVault, remainingAccounts, payload, DAMM_V2_PROGRAM_ID)
+  const dammV2PoolState = getDammV2PoolState(svm, dammv2Pool);
+
+  // claimed token a is swapped by fee vault, so it must be paid to fee vault's account
+  const tokenAAccount = minimumAmountOut
+    ? getOrCreateAtA(
+      svm,
+      signer,
+      dammV2PoolState.tokenAMint,
+      feeVault,
+      getProgramFromFlagDammV2(dammV2PoolState.tokenAFlag)
+    )
+    : getAssociatedTokenAddressSync(
+      dammV2PoolState.tokenAMint,
+      owner.publicKey,
+      true,
+      getProgramFromFlagDammV2(dammV2PoolState.tokenAFlag)
+    );
+
+  const remainingAccounts = getDammV2ClaimPositionFeeAccounts(
+    svm,
+    feeVault,
+    tokenVault,
+    dammv2Pool,
+    position,
+    positionNftAccount,
+    tokenAAccount
+  );
+
+  const payload = getDammV2ClaimPositionFeePayload();
+
+  if (minimumAmountOut) {
+    await fundByClaimingFeeAndSwap(svm, signer, feeVault, tokenVault, remainingAccounts, payload, DAMM_V2_PROGRAM_ID, minimumAmountOut, minClaimedAmount ?? new BN(0), errorCode)
+  } else {
+    await fundByClaimingFee(svm, signer, feeVault, tokenVault, remainingAccounts, payload, DAMM_V2_PROGRAM_ID, errorCode, minClaimedAmount ?? null)
+  }
 
 }
 
@@ -277,6 +398,96 @@ export async function claimDammV2Reward(
 
 }
 
+export async function claimDammV2PartnerFee(
+  svm: LiteSVM,
+  signer: Keypair,
+  feeVault: PublicKey,
+  tokenVault: PublicKey,
+  dammv2Pool: PublicKey,
+) {
+  const dammV2PoolState = getDammV2PoolState(svm, dammv2Pool);
+
+  const tokenAAccount = getOrCreateAtA(
+    svm,
+    signer,
+    dammV2PoolState.tokenAMint,
+    signer.publicKey,
+    getProgramFromFlagDammV2(dammV2PoolState.tokenAFlag)
+  );
+
+  const remainingAccounts = [
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: deriveDammV2PoolAuthority(),
+    },
+    {
+      isSigner: false,
+      isWritable: true,
+      pubkey: dammv2Pool,
+    },
+    {
+      isSigner: false,
+      isWritable: true,
+      pubkey: tokenAAccount,
+    },
+    {
+      isSigner: false,
+      isWritable: true,
+      pubkey: tokenVault,
+    },
+    {
+      isSigner: false,
+      isWritable: true,
+      pubkey: dammV2PoolState.tokenAVault,
+    },
+    {
+      isSigner: false,
+      isWritable: true,
+      pubkey: dammV2PoolState.tokenBVault,
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: dammV2PoolState.tokenAMint,
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: dammV2PoolState.tokenBMint,
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: feeVault,
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: getProgramFromFlagDammV2(dammV2PoolState.tokenAFlag),
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: getProgramFromFlagDammV2(dammV2PoolState.tokenBFlag),
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: deriveDammV2EventAuthority(),
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: DAMM_V2_PROGRAM_ID,
+    },
+  ];
+
+  const claimPartnerFeeDisc = CpAmmIDL.instructions.find(instruction => instruction.name === "claim_partner_fee").discriminator;
+  const payload = Buffer.concat([Buffer.from(claimPartnerFeeDisc), U64_MAX.toBuffer(), U64_MAX.toBuffer()])
+  await fundByClaimingFee(svm, signer, feeVault, tokenVault, remainingAccounts, payload, DAMM_V2_PROGRAM_ID)
+}
+
 export async function claimDbcCreatorTradingFee(
   svm: LiteSVM,
   signer: Keypair,
@@ -671,3 +882,110 @@ export async function withdrawMigrationFee(
   const payload = Buffer.concat([Buffer.from(withdrawMigrationFeeDisc), Buffer.from([isPartner])])
   await fundByClaimingFee(svm, signer, feeVault, tokenVault, remainingAccounts, payload, DBC_PROGRAM_ID);
 }
+
+export async function withdrawDbcLeftover(
+  svm: LiteSVM,
+  signer: Keypair,
+  feeVault: PublicKey,
+  tokenVault: PublicKey,
+  poolConfig: PublicKey,
+  virtualPool: PublicKey
+) {
+  const virtualPoolState = getVirtualPoolState(svm, virtualPool);
+
+  // leftover is only withdrawn to ATA of leftover receiver, then swept into token vault
+  const feeVaultBaseAccount = getOrCreateAtA(
+    svm,
+    signer,
+    virtualPoolState.baseMint,
+    feeVault,
+    TOKEN_2022_PROGRAM_ID
+  );
+
+  const remainingAccounts = [
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: deriveDbcPoolAuthority(),
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: poolConfig,
+    },
+    {
+      isSigner: false,
+      isWritable: true,
+      pubkey: virtualPool,
+    },
+    {
+      isSigner: false,
+      isWritable: true,
+      pubkey: feeVaultBaseAccount,
+    },
+    {
+      isSigner: false,
+      isWritable: true,
+      pubkey: virtualPoolState.baseVault,
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: virtualPoolState.baseMint,
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: feeVault,
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: TOKEN_2022_PROGRAM_ID,
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: deriveDbcEventAuthority(),
+    },
+    {
+      isSigner: false,
+      isWritable: false,
+      pubkey: DBC_PROGRAM_ID,
+    },
+  ]
+  const withdrawLeftoverDisc = DynamicBondingCurveIDL.instructions.find(instruction => instruction.name === "withdraw_leftover").discriminator;
+  const payload = Buffer.from(withdrawLeftoverDisc)
+  await fundByClaimingFee(svm, signer, feeVault, tokenVault, remainingAccounts, payload, DBC_PROGRAM_ID);
+}
+
+export async function permanentLockPosition(
+  svm: LiteSVM,
+  owner: Keypair,
+  feeVault: PublicKey,
+  dammv2Pool: PublicKey,
+  position: PublicKey,
+  positionNftAccount: PublicKey,
+  permanentLockLiquidity: BN
+) {
+  const program = createProgram();
+
+  const tx = await program.methods
+    .permanentLockPosition(permanentLockLiquidity)
+    .accountsPartial({
+      feeVault,
+      pool: dammv2Pool,
+      position,
+      positionNftAccount,
+      owner: owner.publicKey,
+      dammV2EventAuthority: deriveDammV2EventAuthority(),
+      dammV2Program: DAMM_V2_PROGRAM_ID,
+    })
+    .transaction();
+
+  tx.recentBlockhash = svm.latestBlockhash();
+  tx.sign(owner);
+
+  const result = sendTransactionOrExpectThrowError(svm, tx);
+  recordComputeUnits("permanent_lock_position", result);
+}
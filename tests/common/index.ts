@@ -18,10 +18,14 @@ import {
   createAssociatedTokenAccountInstruction,
   createCloseAccountInstruction,
   createInitializeMint2Instruction,
+  createInitializeTransferFeeConfigInstruction,
   createMintToInstruction,
+  ExtensionType,
   getAssociatedTokenAddressSync,
+  getMintLen,
   MINT_SIZE,
   NATIVE_MINT,
+  TOKEN_2022_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import {
@@ -41,6 +45,10 @@ export type UserShare = IdlTypes<DynamicFeeSharing>["userShare"];
 
 export type FeeVault = IdlAccounts<DynamicFeeSharing>["feeVault"];
 
+export type Config = IdlAccounts<DynamicFeeSharing>["config"];
+
+export type FeeVaultMetadata = IdlAccounts<DynamicFeeSharing>["feeVaultMetadata"];
+
 export type DynamicFeeSharingProgram = Program<DynamicFeeSharing>;
 
 export const TOKEN_DECIMALS = 9;
@@ -70,6 +78,51 @@ export function getFeeVault(svm: LiteSVM, feeVault: PublicKey): FeeVault {
   return program.coder.accounts.decode("feeVault", Buffer.from(account.data));
 }
 
+// fee vault accounts created before versioning can't be created by the program anymore, so
+// an existing vault is rewritten in the layout of that program: 640 bytes after discriminator
+// with `padding_0: [u8; 13]`, `padding: [u128; 4]` and `padding: [u8; 16]` in every user
+export const FEE_VAULT_V0_SIZE = 8 + 640;
+
+export function setFeeVaultV0(svm: LiteSVM, feeVault: PublicKey) {
+  const account = svm.getAccount(feeVault);
+  const state = getFeeVault(svm, feeVault);
+  const data = Buffer.alloc(FEE_VAULT_V0_SIZE);
+  Buffer.from(account.data).copy(data, 0, 0, 8);
+  let offset = 8;
+  for (const key of [state.owner, state.tokenMint, state.tokenVault]) {
+    key.toBuffer().copy(data, offset);
+    offset += 32;
+  }
+  data.writeUInt8(state.tokenFlag, offset);
+  data.writeUInt8(state.feeVaultType, offset + 1);
+  data.writeUInt8(state.feeVaultBump, offset + 2);
+  offset += 3 + 13;
+  data.writeUInt32LE(state.totalShare, offset);
+  offset += 4 + 4;
+  state.totalFundedFee.toArrayLike(Buffer, "le", 8).copy(data, offset);
+  offset += 8;
+  state.feePerShare.toArrayLike(Buffer, "le", 16).copy(data, offset);
+  offset += 16;
+  state.base.toBuffer().copy(data, offset);
+  offset += 32 + 64;
+  for (const user of state.users) {
+    user.address.toBuffer().copy(data, offset);
+    data.writeUInt32LE(user.share, offset + 32);
+    user.feeClaimed.toArrayLike(Buffer, "le", 8).copy(data, offset + 40);
+    user.feePerShareCheckpoint
+      .toArrayLike(Buffer, "le", 16)
+      .copy(data, offset + 64);
+    offset += 80;
+  }
+
+  svm.setAccount(feeVault, {
+    data,
+    executable: false,
+    lamports: Number(svm.getRent().minimumBalance(BigInt(FEE_VAULT_V0_SIZE))),
+    owner: account.owner,
+  });
+}
+
 export function deriveFeeVaultAuthorityAddress(): PublicKey {
   const program = createProgram();
   return PublicKey.findProgramAddressSync(
@@ -78,6 +131,54 @@ export function deriveFeeVaultAuthorityAddress(): PublicKey {
   )[0];
 }
 
+export function deriveConfigAddress(): PublicKey {
+  const program = createProgram();
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  )[0];
+}
+
+// config can only be initialized by program upgrade authority, so it is mocked in tests
+export async function setConfig(
+  svm: LiteSVM,
+  admin: PublicKey,
+  treasury: PublicKey,
+  protocolFeeBps: number,
+  pauseFlags = 0,
+  transferHookPrograms: PublicKey[] = []
+): Promise<PublicKey> {
+  const program = createProgram();
+  const config = deriveConfigAddress();
+  const data = await program.coder.accounts.encode("config", {
+    admin,
+    treasury,
+    protocolFeeBps,
+    pauseFlags,
+    padding0: new Array(5).fill(0),
+    transferHookPrograms: [
+      ...transferHookPrograms,
+      ...new Array(4 - transferHookPrograms.length).fill(PublicKey.default),
+    ],
+    padding: new Array(8).fill(new BN(0)),
+  });
+  svm.setAccount(config, {
+    data,
+    executable: false,
+    lamports: LAMPORTS_PER_SOL,
+    owner: program.programId,
+  });
+  return config;
+}
+
+export function deriveEventAuthorityAddress(): PublicKey {
+  const program = createProgram();
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("__event_authority")],
+    program.programId
+  )[0];
+}
+
 export function deriveTokenVaultAddress(feeVault: PublicKey): PublicKey {
   const program = createProgram();
   return PublicKey.findProgramAddressSync(
@@ -86,6 +187,32 @@ export function deriveTokenVaultAddress(feeVault: PublicKey): PublicKey {
   )[0];
 }
 
+export function deriveFeeVaultMetadataAddress(feeVault: PublicKey): PublicKey {
+  const program = createProgram();
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("fee_vault_metadata"), feeVault.toBuffer()],
+    program.programId
+  )[0];
+}
+
+export function getFeeVaultMetadata(
+  svm: LiteSVM,
+  feeVaultMetadata: PublicKey
+): FeeVaultMetadata {
+  const program = createProgram();
+  const account = svm.getAccount(feeVaultMetadata);
+  return program.coder.accounts.decode(
+    "feeVaultMetadata",
+    Buffer.from(account.data)
+  );
+}
+
+export function decodeMetadataString(bytes: number[]): string {
+  const data = Buffer.from(bytes);
+  const end = data.indexOf(0);
+  return data.subarray(0, end === -1 ? data.length : end).toString("utf8");
+}
+
 export function deriveFeeVaultPdaAddress(
   base: PublicKey,
   tokenMint: PublicKey
@@ -132,21 +259,74 @@ export function createToken(
   return mintKeypair.publicKey;
 }
 
+export function createToken2022(
+  svm: LiteSVM,
+  payer: Keypair,
+  mintAuthority: PublicKey,
+  transferFeeBps?: number
+): PublicKey {
+  const mintKeypair = Keypair.generate();
+  const extensions =
+    transferFeeBps === undefined ? [] : [ExtensionType.TransferFeeConfig];
+  const space = getMintLen(extensions);
+  const lamports = svm.getRent().minimumBalance(BigInt(space));
+
+  let transaction = new Transaction().add(
+    SystemProgram.createAccount({
+      fromPubkey: payer.publicKey,
+      newAccountPubkey: mintKeypair.publicKey,
+      space,
+      lamports: Number(lamports.toString()),
+      programId: TOKEN_2022_PROGRAM_ID,
+    })
+  );
+  if (transferFeeBps !== undefined) {
+    transaction.add(
+      createInitializeTransferFeeConfigInstruction(
+        mintKeypair.publicKey,
+        mintAuthority,
+        mintAuthority,
+        transferFeeBps,
+        BigInt(U64_MAX.toString()),
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+  }
+  transaction.add(
+    createInitializeMint2Instruction(
+      mintKeypair.publicKey,
+      TOKEN_DECIMALS,
+      mintAuthority,
+      null,
+      TOKEN_2022_PROGRAM_ID
+    )
+  );
+  transaction.recentBlockhash = svm.latestBlockhash();
+  transaction.sign(payer, mintKeypair);
+
+  svm.sendTransaction(transaction);
+
+  return mintKeypair.publicKey;
+}
+
 export function mintToken(
   svm: LiteSVM,
   payer: Keypair,
   mint: PublicKey,
   mintAuthority: Keypair,
   toWallet: PublicKey,
-  amount?: number
+  amount?: number,
+  tokenProgram = TOKEN_PROGRAM_ID
 ) {
-  const destination = getOrCreateAtA(svm, payer, mint, toWallet);
+  const destination = getOrCreateAtA(svm, payer, mint, toWallet, tokenProgram);
 
   const mintIx = createMintToInstruction(
     mint,
     destination,
     mintAuthority.publicKey,
-    amount ?? RAW_AMOUNT
+    amount ?? RAW_AMOUNT,
+    [],
+    tokenProgram
   );
 
   let transaction = new Transaction();
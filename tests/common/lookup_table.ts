@@ -0,0 +1,179 @@
+import {
+  AccountMeta,
+  AddressLookupTableAccount,
+  AddressLookupTableProgram,
+  Keypair,
+  PublicKey,
+  TransactionInstruction,
+  TransactionMessage,
+  VersionedTransaction,
+} from "@solana/web3.js";
+import { LiteSVM } from "litesvm";
+import {
+  deriveConfigAddress,
+  deriveEventAuthorityAddress,
+  deriveFeeVaultAuthorityAddress,
+  DYNAMIC_FEE_SHARING_PROGRAM_ID,
+} from ".";
+
+// keeps each extend instruction small enough to be sent in a legacy transaction
+export const MAX_EXTEND_LOOKUP_TABLE_ADDRESSES = 20;
+
+const LOOKUP_TABLE_META_SIZE = 56;
+
+// fund_by_claiming_fee against DAMM v2 or DBC takes 15+ accounts, so batching several
+// claims only fits in a v0 transaction with the accounts of each vault in a lookup table
+export function getFeeVaultLookupTableAddresses(
+  feeVault: PublicKey,
+  tokenVault: PublicKey,
+  sourceProgram: PublicKey,
+  poolAccounts: (PublicKey | AccountMeta)[]
+): PublicKey[] {
+  const addresses = [
+    feeVault,
+    tokenVault,
+    deriveFeeVaultAuthorityAddress(),
+    deriveConfigAddress(),
+    deriveEventAuthorityAddress(),
+    DYNAMIC_FEE_SHARING_PROGRAM_ID,
+    sourceProgram,
+    ...poolAccounts.map((account) =>
+      account instanceof PublicKey ? account : account.pubkey
+    ),
+  ];
+  return addresses.filter(
+    (address, i) => addresses.findIndex((a) => a.equals(address)) === i
+  );
+}
+
+export function extendLookupTableInstructions(
+  lookupTable: PublicKey,
+  authority: PublicKey,
+  payer: PublicKey,
+  addresses: PublicKey[]
+): TransactionInstruction[] {
+  const instructions = [];
+  for (let i = 0; i < addresses.length; i += MAX_EXTEND_LOOKUP_TABLE_ADDRESSES) {
+    instructions.push(
+      AddressLookupTableProgram.extendLookupTable({
+        lookupTable,
+        authority,
+        payer,
+        addresses: addresses.slice(i, i + MAX_EXTEND_LOOKUP_TABLE_ADDRESSES),
+      })
+    );
+  }
+  return instructions;
+}
+
+// each group of instructions is sent in its own transaction, addresses can be used once the
+// slot of the last extend has passed
+export function createLookupTableInstructions(
+  authority: PublicKey,
+  payer: PublicKey,
+  recentSlot: number,
+  addresses: PublicKey[]
+): {
+  lookupTable: PublicKey;
+  instructions: TransactionInstruction[][];
+} {
+  const [createIx, lookupTable] = AddressLookupTableProgram.createLookupTable({
+    authority,
+    payer,
+    recentSlot,
+  });
+  const [firstExtendIx, ...extendIxs] = extendLookupTableInstructions(
+    lookupTable,
+    authority,
+    payer,
+    addresses
+  );
+
+  return {
+    lookupTable,
+    instructions: [
+      firstExtendIx ? [createIx, firstExtendIx] : [createIx],
+      ...extendIxs.map((ix) => [ix]),
+    ],
+  };
+}
+
+// lookup table derivation requires a recent slot of slot hashes sysvar, so it is mocked in
+// tests with all addresses active
+export function setLookupTable(
+  svm: LiteSVM,
+  authority: PublicKey,
+  addresses: PublicKey[]
+): PublicKey {
+  const [, lookupTable] = AddressLookupTableProgram.createLookupTable({
+    authority,
+    payer: authority,
+    recentSlot: Number(svm.getClock().slot),
+  });
+
+  const data = Buffer.alloc(LOOKUP_TABLE_META_SIZE + addresses.length * 32);
+  // lookup table type, deactivation slot, last extended slot and its start index
+  data.writeUInt32LE(1, 0);
+  data.writeBigUInt64LE(BigInt("18446744073709551615"), 4);
+  data.writeBigUInt64LE(BigInt(0), 12);
+  data.writeUInt8(addresses.length, 20);
+  data.writeUInt8(1, 21);
+  authority.toBuffer().copy(data, 22);
+  addresses.forEach((address, i) =>
+    address.toBuffer().copy(data, LOOKUP_TABLE_META_SIZE + i * 32)
+  );
+
+  svm.setAccount(lookupTable, {
+    data,
+    executable: false,
+    lamports: Number(svm.getRent().minimumBalance(BigInt(data.length))),
+    owner: AddressLookupTableProgram.programId,
+  });
+
+  return lookupTable;
+}
+
+export function getLookupTableAccount(
+  svm: LiteSVM,
+  lookupTable: PublicKey
+): AddressLookupTableAccount {
+  const account = svm.getAccount(lookupTable);
+  return new AddressLookupTableAccount({
+    key: lookupTable,
+    state: AddressLookupTableAccount.deserialize(Buffer.from(account.data)),
+  });
+}
+
+export function buildV0Transaction(
+  svm: LiteSVM,
+  payer: Keypair,
+  instructions: TransactionInstruction[],
+  lookupTables: AddressLookupTableAccount[],
+  signers: Keypair[] = []
+): VersionedTransaction {
+  const message = new TransactionMessage({
+    payerKey: payer.publicKey,
+    recentBlockhash: svm.latestBlockhash(),
+    instructions,
+  }).compileToV0Message(lookupTables);
+
+  const tx = new VersionedTransaction(message);
+  tx.sign([payer, ...signers]);
+
+  return tx;
+}
+
+// builds a v0 transaction from method builders of any dynamic fee sharing instruction,
+// e.g. program.methods.claimFee(index).accountsPartial({...})
+export async function buildV0TransactionFromMethods(
+  svm: LiteSVM,
+  payer: Keypair,
+  methods: { instruction(): Promise<TransactionInstruction> }[],
+  lookupTables: AddressLookupTableAccount[],
+  signers: Keypair[] = []
+): Promise<VersionedTransaction> {
+  const instructions = await Promise.all(
+    methods.map((method) => method.instruction())
+  );
+  return buildV0Transaction(svm, payer, instructions, lookupTables, signers);
+}
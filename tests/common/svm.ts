@@ -4,6 +4,7 @@ import {
   PublicKey,
   SystemProgram,
   Transaction,
+  VersionedTransaction,
 } from "@solana/web3.js";
 
 import {
@@ -19,7 +20,8 @@ import { DAMM_V2_PROGRAM_ID } from "./damm_v2";
 import { AccountLayout } from "@solana/spl-token";
 import { DBC_PROGRAM_ID } from "./dbc";
 
-export function startSvm() {
+// dammV2ProgramPath replaces DAMM v2 with another program deployed at its address
+export function startSvm(dammV2ProgramPath = "./tests/fixtures/damm_v2.so") {
   const svm = new LiteSVM();
   svm.addProgramFromFile(
     new PublicKey(DYNAMIC_FEE_SHARING_PROGRAM_ID),
@@ -28,7 +30,7 @@ export function startSvm() {
 
   svm.addProgramFromFile(
     new PublicKey(DAMM_V2_PROGRAM_ID),
-    "./tests/fixtures/damm_v2.so"
+    dammV2ProgramPath
   );
 
   svm.addProgramFromFile(
@@ -53,7 +55,7 @@ export function startSvm() {
 
 export function sendTransactionOrExpectThrowError(
   svm: LiteSVM,
-  transaction: Transaction,
+  transaction: Transaction | VersionedTransaction,
   logging = false,
   errorCode?: number
 ) {
@@ -0,0 +1,389 @@
+import { LiteSVM, TransactionMetadata } from "litesvm";
+import {
+  PublicKey,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  createProgram,
+  createToken,
+  createToken2022,
+  deriveFeeVaultAuthorityAddress,
+  deriveFeeVaultMetadataAddress,
+  deriveFeeVaultPdaAddress,
+  deriveTokenVaultAddress,
+  DynamicFeeSharingProgram,
+  generateUsers,
+  getOrCreateAtA,
+  InitializeFeeVaultParameters,
+  mintToken,
+  TOKEN_DECIMALS,
+} from "./common";
+import {
+  checkComputeUnitBaseline,
+  recordComputeUnits,
+} from "./common/compute_unit";
+import { BN } from "bn.js";
+import {
+  getAssociatedTokenAddressSync,
+  NATIVE_MINT,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+import DynamicFeeSharingIDL from "../target/idl/dynamic_fee_sharing.json";
+
+// Compute units of whitelisted actions of fund_by_claiming_fee are recorded by the DAMM v2 and
+// DBC test suites, so the baseline is checked once every test file has run
+after(() => {
+  checkComputeUnitBaseline();
+});
+
+const MINT_KINDS = ["spl_token", "token_2022", "token_2022_transfer_fee"];
+
+describe("Compute unit", () => {
+  let program: DynamicFeeSharingProgram;
+  let svm: LiteSVM;
+  let admin: Keypair;
+  let funder: Keypair;
+  let vaultOwner: Keypair;
+
+  beforeEach(async () => {
+    program = createProgram();
+    svm = new LiteSVM();
+    svm.addProgramFromFile(
+      new PublicKey(DynamicFeeSharingIDL.address),
+      "./target/deploy/dynamic_fee_sharing.so"
+    );
+
+    admin = Keypair.generate();
+    vaultOwner = Keypair.generate();
+    funder = Keypair.generate();
+
+    svm.airdrop(admin.publicKey, BigInt(10 * LAMPORTS_PER_SOL));
+    svm.airdrop(vaultOwner.publicKey, BigInt(LAMPORTS_PER_SOL));
+    svm.airdrop(funder.publicKey, BigInt(10 * LAMPORTS_PER_SOL));
+  });
+
+  const send = (name: string, tx: Transaction, signers: Keypair[]) => {
+    tx.recentBlockhash = svm.latestBlockhash();
+    tx.sign(...signers);
+    const result = svm.sendTransaction(tx);
+    expect(result).instanceOf(TransactionMetadata);
+    recordComputeUnits(name, result);
+  };
+
+  const newParams = (
+    users: PublicKey[],
+    metadata: InitializeFeeVaultParameters["metadata"] = null
+  ): InitializeFeeVaultParameters => ({
+    vestingDuration: new BN(0),
+    duplicateUserMode: 0,
+    transferFeeMode: 0,
+    allowPermanentDelegate: 0,
+    padding0: [],
+    padding: [],
+    users: users.map((address) => ({
+      address,
+      share: 1000,
+      endTimestamp: new BN(0),
+    })),
+    metadata,
+  });
+
+  const initializeFeeVault = async (
+    name: string,
+    tokenMint: PublicKey,
+    tokenProgram: PublicKey,
+    users: PublicKey[],
+    metadata: InitializeFeeVaultParameters["metadata"] = null
+  ): Promise<PublicKey> => {
+    const feeVault = Keypair.generate();
+    const tx = await program.methods
+      .initializeFeeVault(newParams(users, metadata))
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        feeVaultAuthority: deriveFeeVaultAuthorityAddress(),
+        tokenVault: deriveTokenVaultAddress(feeVault.publicKey),
+        tokenMint,
+        owner: vaultOwner.publicKey,
+        payer: admin.publicKey,
+        tokenProgram,
+        feeVaultMetadata: metadata
+          ? deriveFeeVaultMetadataAddress(feeVault.publicKey)
+          : null,
+      })
+      .transaction();
+    send(name, tx, [admin, feeVault]);
+    return feeVault.publicKey;
+  };
+
+  const fundFee = async (
+    name: string,
+    feeVault: PublicKey,
+    tokenMint: PublicKey,
+    tokenProgram: PublicKey
+  ) => {
+    const tx = await program.methods
+      .fundFee(new BN(1_000 * 10 ** TOKEN_DECIMALS))
+      .accountsPartial({
+        feeVault,
+        tokenVault: deriveTokenVaultAddress(feeVault),
+        tokenMint,
+        fundTokenVault: getAssociatedTokenAddressSync(
+          tokenMint,
+          funder.publicKey,
+          false,
+          tokenProgram
+        ),
+        funder: funder.publicKey,
+        tokenProgram,
+      })
+      .transaction();
+    send(name, tx, [funder]);
+  };
+
+  const claimFee = async (
+    name: string,
+    feeVault: PublicKey,
+    tokenMint: PublicKey,
+    tokenProgram: PublicKey,
+    user: Keypair
+  ) => {
+    const userTokenVault = getOrCreateAtA(
+      svm,
+      user,
+      tokenMint,
+      user.publicKey,
+      tokenProgram
+    );
+    const tx = await program.methods
+      .claimFee(0)
+      .accountsPartial({
+        feeVault,
+        tokenMint,
+        tokenVault: deriveTokenVaultAddress(feeVault),
+        userTokenVault,
+        user: user.publicKey,
+        tokenProgram,
+      })
+      .transaction();
+    send(name, tx, [user]);
+  };
+
+  MINT_KINDS.forEach((mintKind) => {
+    it(`initialize_fee_vault, fund_fee and claim_fee with ${mintKind}`, async () => {
+      let tokenMint: PublicKey;
+      let tokenProgram: PublicKey;
+      if (mintKind == "spl_token") {
+        tokenMint = createToken(svm, admin, admin.publicKey, null);
+        tokenProgram = TOKEN_PROGRAM_ID;
+      } else {
+        const transferFeeBps =
+          mintKind == "token_2022_transfer_fee" ? 100 : undefined;
+        tokenMint = createToken2022(
+          svm,
+          admin,
+          admin.publicKey,
+          transferFeeBps
+        );
+        tokenProgram = TOKEN_2022_PROGRAM_ID;
+      }
+      mintToken(
+        svm,
+        admin,
+        tokenMint,
+        admin,
+        funder.publicKey,
+        undefined,
+        tokenProgram
+      );
+
+      const [userA, userB] = generateUsers(svm, 2);
+      const feeVault = await initializeFeeVault(
+        `initialize_fee_vault/${mintKind}`,
+        tokenMint,
+        tokenProgram,
+        [userA.publicKey, userB.publicKey]
+      );
+      await fundFee(`fund_fee/${mintKind}`, feeVault, tokenMint, tokenProgram);
+      await claimFee(
+        `claim_fee/${mintKind}`,
+        feeVault,
+        tokenMint,
+        tokenProgram,
+        userA
+      );
+    });
+  });
+
+  it("initialize_fee_vault_pda and fee vault metadata", async () => {
+    const tokenMint = createToken(svm, admin, admin.publicKey, null);
+    const users = generateUsers(svm, 2).map((user) => user.publicKey);
+
+    const base = Keypair.generate();
+    const feeVaultPda = deriveFeeVaultPdaAddress(base.publicKey, tokenMint);
+    const initializeFeeVaultPdaTx = await program.methods
+      .initializeFeeVaultPda(newParams(users))
+      .accountsPartial({
+        feeVault: feeVaultPda,
+        base: base.publicKey,
+        feeVaultAuthority: deriveFeeVaultAuthorityAddress(),
+        tokenVault: deriveTokenVaultAddress(feeVaultPda),
+        tokenMint,
+        owner: vaultOwner.publicKey,
+        payer: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    send("initialize_fee_vault_pda/spl_token", initializeFeeVaultPdaTx, [
+      admin,
+      base,
+    ]);
+
+    const metadata = {
+      name: "Partner vault",
+      uri: "https://example.com/vault.json",
+      labels: ["Partner A", "Partner B"],
+    };
+    await initializeFeeVault(
+      "initialize_fee_vault/spl_token_with_metadata",
+      tokenMint,
+      TOKEN_PROGRAM_ID,
+      users,
+      metadata
+    );
+
+    const feeVaultMetadata = deriveFeeVaultMetadataAddress(feeVaultPda);
+    const initializeMetadataTx = await program.methods
+      .initializeFeeVaultMetadata(metadata)
+      .accountsPartial({
+        feeVault: feeVaultPda,
+        feeVaultMetadata,
+        owner: vaultOwner.publicKey,
+        payer: admin.publicKey,
+      })
+      .transaction();
+    send("initialize_fee_vault_metadata", initializeMetadataTx, [
+      admin,
+      vaultOwner,
+    ]);
+
+    const updateMetadataTx = await program.methods
+      .updateFeeVaultMetadata({ ...metadata, labels: ["Partner A"] })
+      .accountsPartial({
+        feeVault: feeVaultPda,
+        feeVaultMetadata,
+        owner: vaultOwner.publicKey,
+      })
+      .transaction();
+    send("update_fee_vault_metadata", updateMetadataTx, [vaultOwner]);
+  });
+
+  it("freeze_vault and unfreeze_vault", async () => {
+    const tokenMint = createToken(svm, admin, admin.publicKey, null);
+    const users = generateUsers(svm, 2).map((user) => user.publicKey);
+    const feeVault = await initializeFeeVault(
+      "initialize_fee_vault/spl_token",
+      tokenMint,
+      TOKEN_PROGRAM_ID,
+      users
+    );
+
+    const freezeTx = await program.methods
+      .freezeVault(true)
+      .accountsPartial({ feeVault, owner: vaultOwner.publicKey })
+      .transaction();
+    send("freeze_vault", freezeTx, [vaultOwner]);
+
+    const unfreezeTx = await program.methods
+      .unfreezeVault()
+      .accountsPartial({ feeVault, owner: vaultOwner.publicKey })
+      .transaction();
+    send("unfreeze_vault", unfreezeTx, [vaultOwner]);
+  });
+
+  it("fund_fee_with_sol and claim_fee_and_unwrap", async () => {
+    const [userA, userB] = generateUsers(svm, 2);
+    const feeVault = await initializeFeeVault(
+      "initialize_fee_vault/native_mint",
+      NATIVE_MINT,
+      TOKEN_PROGRAM_ID,
+      [userA.publicKey, userB.publicKey]
+    );
+    const tokenVault = deriveTokenVaultAddress(feeVault);
+
+    const fundFeeTx = await program.methods
+      .fundFeeWithSol(new BN(LAMPORTS_PER_SOL))
+      .accountsPartial({
+        feeVault,
+        tokenVault,
+        tokenMint: NATIVE_MINT,
+        funder: funder.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    send("fund_fee_with_sol", fundFeeTx, [funder]);
+
+    const tempWsolAccount = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("temp_wsol"),
+        feeVault.toBuffer(),
+        userA.publicKey.toBuffer(),
+      ],
+      program.programId
+    )[0];
+    const claimFeeTx = await program.methods
+      .claimFeeAndUnwrap(0)
+      .accountsPartial({
+        feeVault,
+        tokenMint: NATIVE_MINT,
+        tokenVault,
+        tempWsolAccount,
+        user: userA.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    send("claim_fee_and_unwrap", claimFeeTx, [userA]);
+  });
+
+  it("pull_from_parent", async () => {
+    const tokenMint = createToken(svm, admin, admin.publicKey, null);
+    mintToken(svm, admin, tokenMint, admin, funder.publicKey);
+
+    const users = generateUsers(svm, 2).map((user) => user.publicKey);
+    const childFeeVault = await initializeFeeVault(
+      "initialize_fee_vault/spl_token",
+      tokenMint,
+      TOKEN_PROGRAM_ID,
+      users
+    );
+    const parentFeeVault = await initializeFeeVault(
+      "initialize_fee_vault/spl_token",
+      tokenMint,
+      TOKEN_PROGRAM_ID,
+      [childFeeVault, users[0]]
+    );
+    await fundFee(
+      "fund_fee/spl_token",
+      parentFeeVault,
+      tokenMint,
+      TOKEN_PROGRAM_ID
+    );
+
+    const pullTx = await program.methods
+      .pullFromParent(0)
+      .accountsPartial({
+        parentFeeVault,
+        parentTokenVault: deriveTokenVaultAddress(parentFeeVault),
+        childFeeVault,
+        childTokenVault: deriveTokenVaultAddress(childFeeVault),
+        tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    send("pull_from_parent", pullTx, [admin]);
+  });
+});
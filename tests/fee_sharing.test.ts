@@ -3,16 +3,22 @@ import { PublicKey, Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
 import {
   createProgram,
   createToken,
+  decodeMetadataString,
   deriveFeeVaultAuthorityAddress,
+  deriveFeeVaultMetadataAddress,
   deriveTokenVaultAddress,
   DynamicFeeSharingProgram,
   expectThrowsErrorCode,
+  FEE_VAULT_V0_SIZE,
   generateUsers,
   getFeeVault,
+  getFeeVaultMetadata,
   getOrCreateAtA,
   getProgramErrorCodeHexString,
   InitializeFeeVaultParameters,
   mintToken,
+  setConfig,
+  setFeeVaultV0,
   TOKEN_DECIMALS,
 } from "./common";
 import { TOKEN_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
@@ -20,8 +26,10 @@ import { BN } from "bn.js";
 import {
   AccountLayout,
   getAssociatedTokenAddressSync,
+  NATIVE_MINT,
 } from "@solana/spl-token";
 import { expect } from "chai";
+import { getTokenBalance, warpToTimestamp } from "./common/svm";
 
 import DynamicFeeSharingIDL from "../target/idl/dynamic_fee_sharing.json";
 
@@ -62,12 +70,19 @@ describe("Fee vault sharing", () => {
       return {
         address: item.publicKey,
         share: 1000,
+        endTimestamp: new BN(0),
       };
     });
 
     const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
       padding: [],
       users,
+      metadata: null,
     };
 
     const feeVault = Keypair.generate();
@@ -98,8 +113,14 @@ describe("Fee vault sharing", () => {
     const users = [];
 
     const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
       padding: [],
       users,
+      metadata: null,
     };
 
     const feeVault = Keypair.generate();
@@ -126,18 +147,1043 @@ describe("Fee vault sharing", () => {
     expectThrowsErrorCode(svm.sendTransaction(tx), errorCode);
   });
 
+  it("Fail to create with duplicated user", async () => {
+    const [duplicatedUser, otherUser] = generateUsers(svm, 2);
+    const users = [duplicatedUser, otherUser, duplicatedUser].map((item) => {
+      return {
+        address: item.publicKey,
+        share: 1000,
+        endTimestamp: new BN(0),
+      };
+    });
+
+    const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
+      padding: [],
+      users,
+      metadata: null,
+    };
+
+    const feeVault = Keypair.generate();
+    const tokenVault = deriveTokenVaultAddress(feeVault.publicKey);
+    const feeVaultAuthority = deriveFeeVaultAuthorityAddress();
+
+    const tx = await program.methods
+      .initializeFeeVault(params)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        feeVaultAuthority,
+        tokenVault,
+        tokenMint,
+        owner: vaultOwner.publicKey,
+        payer: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+
+    tx.recentBlockhash = svm.latestBlockhash();
+    tx.sign(admin, feeVault);
+
+    const errorCode = getProgramErrorCodeHexString("DuplicatedUser");
+    expectThrowsErrorCode(svm.sendTransaction(tx), errorCode);
+  });
+
+  it("Merge duplicated user into one slot", async () => {
+    const [duplicatedUser, otherUser] = generateUsers(svm, 2);
+    const users = [duplicatedUser, otherUser, duplicatedUser].map((item) => {
+      return {
+        address: item.publicKey,
+        share: 1000,
+        endTimestamp: new BN(0),
+      };
+    });
+
+    const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 1,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
+      padding: [],
+      users,
+      metadata: null,
+    };
+
+    const feeVault = Keypair.generate();
+    const tokenVault = deriveTokenVaultAddress(feeVault.publicKey);
+    const feeVaultAuthority = deriveFeeVaultAuthorityAddress();
+
+    const tx = await program.methods
+      .initializeFeeVault(params)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        feeVaultAuthority,
+        tokenVault,
+        tokenMint,
+        owner: vaultOwner.publicKey,
+        payer: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+
+    tx.recentBlockhash = svm.latestBlockhash();
+    tx.sign(admin, feeVault);
+    expect(svm.sendTransaction(tx)).instanceOf(TransactionMetadata);
+
+    const feeVaultState = getFeeVault(svm, feeVault.publicKey);
+    expect(feeVaultState.totalShare).eq(3000);
+    expect(feeVaultState.users[0].address.toString()).eq(
+      duplicatedUser.publicKey.toString()
+    );
+    expect(feeVaultState.users[0].share).eq(2000);
+    expect(feeVaultState.users[1].share).eq(1000);
+    expect(feeVaultState.users[2].address.equals(PublicKey.default)).to.be
+      .true;
+  });
+
+  it("Expired share stops accruing newly funded fee", async () => {
+    const [expiringUser, otherUser] = generateUsers(svm, 2);
+    const currentTimestamp = new BN(svm.getClock().unixTimestamp.toString());
+    const endTimestamp = currentTimestamp.add(new BN(3600));
+
+    const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
+      padding: [],
+      users: [
+        {
+          address: expiringUser.publicKey,
+          share: 1000,
+          endTimestamp,
+        },
+        {
+          address: otherUser.publicKey,
+          share: 1000,
+          endTimestamp: new BN(0),
+        },
+      ],
+      metadata: null,
+    };
+
+    const feeVault = Keypair.generate();
+    const tokenVault = deriveTokenVaultAddress(feeVault.publicKey);
+    const feeVaultAuthority = deriveFeeVaultAuthorityAddress();
+
+    const tx = await program.methods
+      .initializeFeeVault(params)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        feeVaultAuthority,
+        tokenVault,
+        tokenMint,
+        owner: vaultOwner.publicKey,
+        payer: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+
+    tx.recentBlockhash = svm.latestBlockhash();
+    tx.sign(admin, feeVault);
+    expect(svm.sendTransaction(tx)).instanceOf(TransactionMetadata);
+
+    const fundTokenVault = getAssociatedTokenAddressSync(
+      tokenMint,
+      funder.publicKey
+    );
+    const fundAmount = new BN(1_000 * 10 ** TOKEN_DECIMALS);
+    const fund = async () => {
+      const fundFeeTx = await program.methods
+        .fundFee(fundAmount)
+        .accountsPartial({
+          feeVault: feeVault.publicKey,
+          tokenVault,
+          tokenMint,
+          fundTokenVault,
+          funder: funder.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .transaction();
+      fundFeeTx.recentBlockhash = svm.latestBlockhash();
+      fundFeeTx.sign(funder);
+      expect(svm.sendTransaction(fundFeeTx)).instanceOf(TransactionMetadata);
+    };
+
+    await fund();
+    warpToTimestamp(svm, endTimestamp);
+    svm.expireBlockhash();
+    await fund();
+
+    const feeVaultState = getFeeVault(svm, feeVault.publicKey);
+    expect(feeVaultState.totalShare).eq(1000);
+    expect(feeVaultState.users[0].isExpired).eq(1);
+
+    const expectedFees = [fundAmount.divn(2), fundAmount.muln(3).divn(2)];
+    for (const [i, user] of [expiringUser, otherUser].entries()) {
+      const userTokenVault = getOrCreateAtA(
+        svm,
+        user,
+        tokenMint,
+        user.publicKey
+      );
+      const claimFeeTx = await program.methods
+        .claimFee(i)
+        .accountsPartial({
+          feeVault: feeVault.publicKey,
+          tokenMint,
+          tokenVault,
+          userTokenVault,
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .transaction();
+      claimFeeTx.recentBlockhash = svm.latestBlockhash();
+      claimFeeTx.sign(user);
+      expect(svm.sendTransaction(claimFeeTx)).instanceOf(TransactionMetadata);
+
+      expect(getTokenBalance(svm, userTokenVault).toString()).eq(
+        expectedFees[i].toString()
+      );
+    }
+  });
+
+  it("Charge protocol fee on funded fee", async () => {
+    const [treasury, userA, userB] = generateUsers(svm, 3);
+    const protocolFeeBps = 500;
+    await setConfig(svm, admin.publicKey, treasury.publicKey, protocolFeeBps);
+
+    const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
+      padding: [],
+      users: [
+        {
+          address: userA.publicKey,
+          share: 1000,
+          endTimestamp: new BN(0),
+        },
+        {
+          address: userB.publicKey,
+          share: 1000,
+          endTimestamp: new BN(0),
+        },
+      ],
+      metadata: null,
+    };
+
+    const feeVault = Keypair.generate();
+    const tokenVault = deriveTokenVaultAddress(feeVault.publicKey);
+    const feeVaultAuthority = deriveFeeVaultAuthorityAddress();
+
+    const tx = await program.methods
+      .initializeFeeVault(params)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        feeVaultAuthority,
+        tokenVault,
+        tokenMint,
+        owner: vaultOwner.publicKey,
+        payer: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+
+    tx.recentBlockhash = svm.latestBlockhash();
+    tx.sign(admin, feeVault);
+    expect(svm.sendTransaction(tx)).instanceOf(TransactionMetadata);
+
+    const fundTokenVault = getAssociatedTokenAddressSync(
+      tokenMint,
+      funder.publicKey
+    );
+    const protocolFeeTokenAccount = getOrCreateAtA(
+      svm,
+      admin,
+      tokenMint,
+      treasury.publicKey
+    );
+    const fundAmount = new BN(1_000 * 10 ** TOKEN_DECIMALS);
+
+    // protocol fee token account is required once protocol fee is charged
+    const fundWithoutProtocolFeeAccountTx = await program.methods
+      .fundFee(fundAmount)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        tokenVault,
+        tokenMint,
+        fundTokenVault,
+        funder: funder.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        protocolFeeTokenAccount: null,
+      })
+      .transaction();
+    fundWithoutProtocolFeeAccountTx.recentBlockhash = svm.latestBlockhash();
+    fundWithoutProtocolFeeAccountTx.sign(funder);
+    expectThrowsErrorCode(
+      svm.sendTransaction(fundWithoutProtocolFeeAccountTx),
+      getProgramErrorCodeHexString("InvalidProtocolFeeTokenAccount")
+    );
+
+    const fundFeeTx = await program.methods
+      .fundFee(fundAmount)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        tokenVault,
+        tokenMint,
+        fundTokenVault,
+        funder: funder.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        protocolFeeTokenAccount,
+      })
+      .transaction();
+    fundFeeTx.recentBlockhash = svm.latestBlockhash();
+    fundFeeTx.sign(funder);
+    expect(svm.sendTransaction(fundFeeTx)).instanceOf(TransactionMetadata);
+
+    const protocolFee = fundAmount.muln(protocolFeeBps).divn(10_000);
+    const feeVaultState = getFeeVault(svm, feeVault.publicKey);
+    expect(getTokenBalance(svm, protocolFeeTokenAccount).toString()).eq(
+      protocolFee.toString()
+    );
+    expect(getTokenBalance(svm, tokenVault).toString()).eq(
+      fundAmount.sub(protocolFee).toString()
+    );
+    expect(feeVaultState.totalFundedFee.toString()).eq(
+      fundAmount.sub(protocolFee).toString()
+    );
+  });
+
+  it("Fail to fund fee when funding is paused", async () => {
+    const [userA, userB] = generateUsers(svm, 2);
+    // pause funding only
+    await setConfig(svm, admin.publicKey, admin.publicKey, 0, 1);
+
+    const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
+      padding: [],
+      users: [
+        {
+          address: userA.publicKey,
+          share: 1000,
+          endTimestamp: new BN(0),
+        },
+        {
+          address: userB.publicKey,
+          share: 1000,
+          endTimestamp: new BN(0),
+        },
+      ],
+      metadata: null,
+    };
+
+    const feeVault = Keypair.generate();
+    const tokenVault = deriveTokenVaultAddress(feeVault.publicKey);
+    const feeVaultAuthority = deriveFeeVaultAuthorityAddress();
+
+    const tx = await program.methods
+      .initializeFeeVault(params)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        feeVaultAuthority,
+        tokenVault,
+        tokenMint,
+        owner: vaultOwner.publicKey,
+        payer: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+
+    tx.recentBlockhash = svm.latestBlockhash();
+    tx.sign(admin, feeVault);
+    expect(svm.sendTransaction(tx)).instanceOf(TransactionMetadata);
+
+    const fundTokenVault = getAssociatedTokenAddressSync(
+      tokenMint,
+      funder.publicKey
+    );
+    const fundFeeTx = await program.methods
+      .fundFee(new BN(1_000 * 10 ** TOKEN_DECIMALS))
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        tokenVault,
+        tokenMint,
+        fundTokenVault,
+        funder: funder.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    fundFeeTx.recentBlockhash = svm.latestBlockhash();
+    fundFeeTx.sign(funder);
+    expectThrowsErrorCode(
+      svm.sendTransaction(fundFeeTx),
+      getProgramErrorCodeHexString("Paused")
+    );
+  });
+
+  it("Admin hands over config to new admin", async () => {
+    const config = await setConfig(svm, admin.publicKey, admin.publicKey, 0);
+    const newAdmin = Keypair.generate();
+    svm.airdrop(newAdmin.publicKey, BigInt(LAMPORTS_PER_SOL));
+
+    // unknown pause flag is rejected
+    const invalidPauseFlagsTx = await program.methods
+      .setPauseFlags(1 << 3)
+      .accountsPartial({ config, admin: admin.publicKey })
+      .transaction();
+    invalidPauseFlagsTx.recentBlockhash = svm.latestBlockhash();
+    invalidPauseFlagsTx.sign(admin);
+    expectThrowsErrorCode(
+      svm.sendTransaction(invalidPauseFlagsTx),
+      getProgramErrorCodeHexString("InvalidPauseFlags")
+    );
+
+    const updateAdminTx = await program.methods
+      .updateAdmin()
+      .accountsPartial({
+        config,
+        admin: admin.publicKey,
+        newAdmin: newAdmin.publicKey,
+      })
+      .transaction();
+    updateAdminTx.recentBlockhash = svm.latestBlockhash();
+    updateAdminTx.sign(admin, newAdmin);
+    expect(svm.sendTransaction(updateAdminTx)).instanceOf(TransactionMetadata);
+
+    const configState = program.coder.accounts.decode(
+      "config",
+      Buffer.from(svm.getAccount(config).data)
+    );
+    expect(configState.admin.toBase58()).eq(newAdmin.publicKey.toBase58());
+
+    // old admin is no longer allowed to pause
+    const oldAdminTx = await program.methods
+      .setPauseFlags(1)
+      .accountsPartial({ config, admin: admin.publicKey })
+      .transaction();
+    oldAdminTx.recentBlockhash = svm.latestBlockhash();
+    oldAdminTx.sign(admin);
+    // anchor ConstraintHasOne
+    expectThrowsErrorCode(svm.sendTransaction(oldAdminTx), 2001);
+
+    const newAdminTx = await program.methods
+      .setPauseFlags(1)
+      .accountsPartial({ config, admin: newAdmin.publicKey })
+      .transaction();
+    newAdminTx.recentBlockhash = svm.latestBlockhash();
+    newAdminTx.sign(newAdmin);
+    expect(svm.sendTransaction(newAdminTx)).instanceOf(TransactionMetadata);
+  });
+
+  it("Frozen vault rejects claim until owner unfreezes it", async () => {
+    const [userA, userB] = generateUsers(svm, 2);
+
+    const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
+      padding: [],
+      users: [
+        {
+          address: userA.publicKey,
+          share: 1000,
+          endTimestamp: new BN(0),
+        },
+        {
+          address: userB.publicKey,
+          share: 1000,
+          endTimestamp: new BN(0),
+        },
+      ],
+      metadata: null,
+    };
+
+    const feeVault = Keypair.generate();
+    const tokenVault = deriveTokenVaultAddress(feeVault.publicKey);
+    const feeVaultAuthority = deriveFeeVaultAuthorityAddress();
+
+    const tx = await program.methods
+      .initializeFeeVault(params)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        feeVaultAuthority,
+        tokenVault,
+        tokenMint,
+        owner: vaultOwner.publicKey,
+        payer: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+
+    tx.recentBlockhash = svm.latestBlockhash();
+    tx.sign(admin, feeVault);
+    expect(svm.sendTransaction(tx)).instanceOf(TransactionMetadata);
+
+    const fundTokenVault = getAssociatedTokenAddressSync(
+      tokenMint,
+      funder.publicKey
+    );
+    const fundAmount = new BN(1_000 * 10 ** TOKEN_DECIMALS);
+    const fundFeeTx = await program.methods
+      .fundFee(fundAmount)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        tokenVault,
+        tokenMint,
+        fundTokenVault,
+        funder: funder.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    fundFeeTx.recentBlockhash = svm.latestBlockhash();
+    fundFeeTx.sign(funder);
+    expect(svm.sendTransaction(fundFeeTx)).instanceOf(TransactionMetadata);
+
+    const freezeTx = await program.methods
+      .freezeVault(false)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        owner: vaultOwner.publicKey,
+      })
+      .transaction();
+    freezeTx.recentBlockhash = svm.latestBlockhash();
+    freezeTx.sign(vaultOwner);
+    expect(svm.sendTransaction(freezeTx)).instanceOf(TransactionMetadata);
+    expect(getFeeVault(svm, feeVault.publicKey).isFrozen).eq(1);
+
+    const userTokenVault = getOrCreateAtA(
+      svm,
+      userA,
+      tokenMint,
+      userA.publicKey
+    );
+    const claim = async () => {
+      const claimFeeTx = await program.methods
+        .claimFee(0)
+        .accountsPartial({
+          feeVault: feeVault.publicKey,
+          tokenMint,
+          tokenVault,
+          userTokenVault,
+          user: userA.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .transaction();
+      claimFeeTx.recentBlockhash = svm.latestBlockhash();
+      claimFeeTx.sign(userA);
+      return svm.sendTransaction(claimFeeTx);
+    };
+
+    expectThrowsErrorCode(
+      await claim(),
+      getProgramErrorCodeHexString("VaultFrozen")
+    );
+
+    const unfreezeTx = await program.methods
+      .unfreezeVault()
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        owner: vaultOwner.publicKey,
+      })
+      .transaction();
+    unfreezeTx.recentBlockhash = svm.latestBlockhash();
+    unfreezeTx.sign(vaultOwner);
+    expect(svm.sendTransaction(unfreezeTx)).instanceOf(TransactionMetadata);
+
+    svm.expireBlockhash();
+    expect(await claim()).instanceOf(TransactionMetadata);
+    expect(getTokenBalance(svm, userTokenVault).toString()).eq(
+      fundAmount.divn(2).toString()
+    );
+  });
+
+  it("Fund with SOL and claim fee unwrapped into SOL", async () => {
+    const [userA, userB] = generateUsers(svm, 2);
+
+    const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
+      padding: [],
+      users: [
+        {
+          address: userA.publicKey,
+          share: 1000,
+          endTimestamp: new BN(0),
+        },
+        {
+          address: userB.publicKey,
+          share: 1000,
+          endTimestamp: new BN(0),
+        },
+      ],
+      metadata: null,
+    };
+
+    const feeVault = Keypair.generate();
+    const tokenVault = deriveTokenVaultAddress(feeVault.publicKey);
+    const feeVaultAuthority = deriveFeeVaultAuthorityAddress();
+
+    const tx = await program.methods
+      .initializeFeeVault(params)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        feeVaultAuthority,
+        tokenVault,
+        tokenMint: NATIVE_MINT,
+        owner: vaultOwner.publicKey,
+        payer: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+
+    tx.recentBlockhash = svm.latestBlockhash();
+    tx.sign(admin, feeVault);
+    expect(svm.sendTransaction(tx)).instanceOf(TransactionMetadata);
+
+    const fundAmount = new BN(LAMPORTS_PER_SOL / 2);
+    const fundFeeTx = await program.methods
+      .fundFeeWithSol(fundAmount)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        tokenVault,
+        tokenMint: NATIVE_MINT,
+        funder: funder.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    fundFeeTx.recentBlockhash = svm.latestBlockhash();
+    fundFeeTx.sign(funder);
+    expect(svm.sendTransaction(fundFeeTx)).instanceOf(TransactionMetadata);
+
+    expect(getTokenBalance(svm, tokenVault).toString()).eq(
+      fundAmount.toString()
+    );
+    expect(getFeeVault(svm, feeVault.publicKey).totalFundedFee.toString()).eq(
+      fundAmount.toString()
+    );
+
+    const tempWsolAccount = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("temp_wsol"),
+        feeVault.publicKey.toBuffer(),
+        userA.publicKey.toBuffer(),
+      ],
+      program.programId
+    )[0];
+    const preUserLamports = svm.getBalance(userA.publicKey);
+
+    const claimFeeTx = await program.methods
+      .claimFeeAndUnwrap(0)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        tokenMint: NATIVE_MINT,
+        tokenVault,
+        tempWsolAccount,
+        user: userA.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    claimFeeTx.recentBlockhash = svm.latestBlockhash();
+    claimFeeTx.sign(userA);
+    expect(svm.sendTransaction(claimFeeTx)).instanceOf(TransactionMetadata);
+
+    // rent of temporary wsol account is paid back to user, only signature fee is spent
+    const postUserLamports = svm.getBalance(userA.publicKey);
+    expect((postUserLamports - preUserLamports + BigInt(5000)).toString()).eq(
+      fundAmount.divn(2).toString()
+    );
+    expect(svm.getAccount(tempWsolAccount)).eq(null);
+    expect(getTokenBalance(svm, tokenVault).toString()).eq(
+      fundAmount.divn(2).toString()
+    );
+  });
+
+  it("Set fee vault metadata at init and update it later", async () => {
+    const generatedUser = generateUsers(svm, 2);
+    const users = generatedUser.map((item) => {
+      return {
+        address: item.publicKey,
+        share: 1000,
+        endTimestamp: new BN(0),
+      };
+    });
+
+    const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
+      padding: [],
+      users,
+      metadata: {
+        name: "Partner vault",
+        uri: "",
+        labels: ["Partner A – marketing", "Partner B"],
+      },
+    };
+
+    const feeVault = Keypair.generate();
+    const feeVaultMetadata = deriveFeeVaultMetadataAddress(feeVault.publicKey);
+
+    const tx = await program.methods
+      .initializeFeeVault(params)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        feeVaultAuthority: deriveFeeVaultAuthorityAddress(),
+        tokenVault: deriveTokenVaultAddress(feeVault.publicKey),
+        tokenMint,
+        owner: vaultOwner.publicKey,
+        payer: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        feeVaultMetadata,
+      })
+      .transaction();
+    tx.recentBlockhash = svm.latestBlockhash();
+    tx.sign(admin, feeVault);
+    expect(svm.sendTransaction(tx)).instanceOf(TransactionMetadata);
+
+    let metadata = getFeeVaultMetadata(svm, feeVaultMetadata);
+    expect(metadata.feeVault.toBase58()).eq(feeVault.publicKey.toBase58());
+    expect(decodeMetadataString(metadata.name)).eq("Partner vault");
+    expect(decodeMetadataString(metadata.labels[0])).eq(
+      "Partner A – marketing"
+    );
+
+    const updateTx = await program.methods
+      .updateFeeVaultMetadata({
+        name: "Partner vault",
+        uri: "https://example.com/vault.json",
+        labels: ["Partner A"],
+      })
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        feeVaultMetadata,
+        owner: vaultOwner.publicKey,
+      })
+      .transaction();
+    updateTx.recentBlockhash = svm.latestBlockhash();
+    updateTx.sign(vaultOwner);
+    expect(svm.sendTransaction(updateTx)).instanceOf(TransactionMetadata);
+
+    metadata = getFeeVaultMetadata(svm, feeVaultMetadata);
+    expect(decodeMetadataString(metadata.uri)).eq(
+      "https://example.com/vault.json"
+    );
+    expect(decodeMetadataString(metadata.labels[0])).eq("Partner A");
+    expect(decodeMetadataString(metadata.labels[1])).eq("");
+
+    // more labels than share holders
+    const failedTx = await program.methods
+      .updateFeeVaultMetadata({
+        name: "",
+        uri: "",
+        labels: ["Partner A", "Partner B", "Partner C"],
+      })
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        feeVaultMetadata,
+        owner: vaultOwner.publicKey,
+      })
+      .transaction();
+    failedTx.recentBlockhash = svm.latestBlockhash();
+    failedTx.sign(vaultOwner);
+    expectThrowsErrorCode(
+      svm.sendTransaction(failedTx),
+      getProgramErrorCodeHexString("InvalidFeeVaultMetadata")
+    );
+  });
+
+  it("Child fee vault pulls its share from parent fee vault", async () => {
+    const [unitUserA, unitUserB, otherUnit] = generateUsers(svm, 3);
+    const feeVaultAuthority = deriveFeeVaultAuthorityAddress();
+
+    const initializeFeeVault = async (
+      feeVault: Keypair,
+      users: { address: PublicKey; share: number }[]
+    ) => {
+      const params: InitializeFeeVaultParameters = {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
+        padding: [],
+        users: users.map((user) => ({ ...user, endTimestamp: new BN(0) })),
+        metadata: null,
+      };
+      const tx = await program.methods
+        .initializeFeeVault(params)
+        .accountsPartial({
+          feeVault: feeVault.publicKey,
+          feeVaultAuthority,
+          tokenVault: deriveTokenVaultAddress(feeVault.publicKey),
+          tokenMint,
+          owner: vaultOwner.publicKey,
+          payer: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .transaction();
+      tx.recentBlockhash = svm.latestBlockhash();
+      tx.sign(admin, feeVault);
+      expect(svm.sendTransaction(tx)).instanceOf(TransactionMetadata);
+    };
+
+    const childFeeVault = Keypair.generate();
+    await initializeFeeVault(childFeeVault, [
+      { address: unitUserA.publicKey, share: 1000 },
+      { address: unitUserB.publicKey, share: 3000 },
+    ]);
+    const parentFeeVault = Keypair.generate();
+    await initializeFeeVault(parentFeeVault, [
+      { address: childFeeVault.publicKey, share: 1000 },
+      { address: otherUnit.publicKey, share: 1000 },
+    ]);
+
+    const parentTokenVault = deriveTokenVaultAddress(parentFeeVault.publicKey);
+    const childTokenVault = deriveTokenVaultAddress(childFeeVault.publicKey);
+    const fundAmount = new BN(1_000 * 10 ** TOKEN_DECIMALS);
+    const fundFeeTx = await program.methods
+      .fundFee(fundAmount)
+      .accountsPartial({
+        feeVault: parentFeeVault.publicKey,
+        tokenVault: parentTokenVault,
+        tokenMint,
+        fundTokenVault: getAssociatedTokenAddressSync(
+          tokenMint,
+          funder.publicKey
+        ),
+        funder: funder.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    fundFeeTx.recentBlockhash = svm.latestBlockhash();
+    fundFeeTx.sign(funder);
+    expect(svm.sendTransaction(fundFeeTx)).instanceOf(TransactionMetadata);
+
+    // permissionless, anyone can crank it
+    const pullTx = await program.methods
+      .pullFromParent(0)
+      .accountsPartial({
+        parentFeeVault: parentFeeVault.publicKey,
+        parentTokenVault,
+        childFeeVault: childFeeVault.publicKey,
+        childTokenVault,
+        tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    pullTx.recentBlockhash = svm.latestBlockhash();
+    pullTx.sign(admin);
+    expect(svm.sendTransaction(pullTx)).instanceOf(TransactionMetadata);
+
+    const childShare = fundAmount.divn(2);
+    expect(getTokenBalance(svm, childTokenVault).toString()).eq(
+      childShare.toString()
+    );
+    expect(
+      getFeeVault(svm, childFeeVault.publicKey).totalFundedFee.toString()
+    ).eq(childShare.toString());
+    expect(
+      getFeeVault(svm, parentFeeVault.publicKey).users[0].feeClaimed.toString()
+    ).eq(childShare.toString());
+
+    const userTokenVault = getOrCreateAtA(
+      svm,
+      unitUserB,
+      tokenMint,
+      unitUserB.publicKey
+    );
+    const claimFeeTx = await program.methods
+      .claimFee(1)
+      .accountsPartial({
+        feeVault: childFeeVault.publicKey,
+        tokenMint,
+        tokenVault: childTokenVault,
+        userTokenVault,
+        user: unitUserB.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    claimFeeTx.recentBlockhash = svm.latestBlockhash();
+    claimFeeTx.sign(unitUserB);
+    expect(svm.sendTransaction(claimFeeTx)).instanceOf(TransactionMetadata);
+    expect(getTokenBalance(svm, userTokenVault).toString()).eq(
+      childShare.muln(3).divn(4).toString()
+    );
+  });
+
+  it("Migrate fee vault created before versioning and claim from it", async () => {
+    const [userA, userB] = generateUsers(svm, 2);
+
+    const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
+      padding: [],
+      users: [
+        {
+          address: userA.publicKey,
+          share: 1000,
+          endTimestamp: new BN(0),
+        },
+        {
+          address: userB.publicKey,
+          share: 3000,
+          endTimestamp: new BN(0),
+        },
+      ],
+      metadata: null,
+    };
+
+    const feeVault = Keypair.generate();
+    const tokenVault = deriveTokenVaultAddress(feeVault.publicKey);
+    const feeVaultAuthority = deriveFeeVaultAuthorityAddress();
+
+    const tx = await program.methods
+      .initializeFeeVault(params)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        feeVaultAuthority,
+        tokenVault,
+        tokenMint,
+        owner: vaultOwner.publicKey,
+        payer: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    tx.recentBlockhash = svm.latestBlockhash();
+    tx.sign(admin, feeVault);
+    expect(svm.sendTransaction(tx)).instanceOf(TransactionMetadata);
+
+    const fundTokenVault = getAssociatedTokenAddressSync(
+      tokenMint,
+      funder.publicKey
+    );
+    const fundAmount = new BN(1_000 * 10 ** TOKEN_DECIMALS);
+    const fundFeeTx = await program.methods
+      .fundFee(fundAmount)
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        tokenVault,
+        tokenMint,
+        fundTokenVault,
+        funder: funder.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .transaction();
+    fundFeeTx.recentBlockhash = svm.latestBlockhash();
+    fundFeeTx.sign(funder);
+    expect(svm.sendTransaction(fundFeeTx)).instanceOf(TransactionMetadata);
+
+    const fundedState = getFeeVault(svm, feeVault.publicKey);
+    setFeeVaultV0(svm, feeVault.publicKey);
+    expect(svm.getAccount(feeVault.publicKey).data.length).eq(
+      FEE_VAULT_V0_SIZE
+    );
+
+    const userTokenVault = getOrCreateAtA(
+      svm,
+      userA,
+      tokenMint,
+      userA.publicKey
+    );
+    const claim = async () => {
+      const claimFeeTx = await program.methods
+        .claimFee(0)
+        .accountsPartial({
+          feeVault: feeVault.publicKey,
+          tokenMint,
+          tokenVault,
+          userTokenVault,
+          user: userA.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .transaction();
+      claimFeeTx.recentBlockhash = svm.latestBlockhash();
+      claimFeeTx.sign(userA);
+      return svm.sendTransaction(claimFeeTx);
+    };
+
+    // unmigrated vault is rejected instead of being read out of bounds
+    expectThrowsErrorCode(
+      await claim(),
+      getProgramErrorCodeHexString("InvalidFeeVaultVersion")
+    );
+
+    const migrateTx = await program.methods
+      .migrateFeeVault()
+      .accountsPartial({
+        feeVault: feeVault.publicKey,
+        payer: admin.publicKey,
+      })
+      .transaction();
+    migrateTx.recentBlockhash = svm.latestBlockhash();
+    migrateTx.sign(admin);
+    expect(svm.sendTransaction(migrateTx)).instanceOf(TransactionMetadata);
+
+    // account is extended to current layout, and payer tops up its rent
+    const migratedAccount = svm.getAccount(feeVault.publicKey);
+    expect(migratedAccount.data.length).eq(8 + 768);
+    expect(migratedAccount.lamports.toString()).eq(
+      svm
+        .getRent()
+        .minimumBalance(BigInt(migratedAccount.data.length))
+        .toString()
+    );
+
+    const migratedState = getFeeVault(svm, feeVault.publicKey);
+    expect(migratedState.version).eq(1);
+    expect(migratedState.totalShare).eq(fundedState.totalShare);
+    expect(migratedState.feePerShare.toString()).eq(
+      fundedState.feePerShare.toString()
+    );
+
+    svm.expireBlockhash();
+    expect(await claim()).instanceOf(TransactionMetadata);
+    expect(getTokenBalance(svm, userTokenVault).toString()).eq(
+      fundAmount.divn(4).toString()
+    );
+  });
+
   it("Full flow", async () => {
     const generatedUser = generateUsers(svm, 5); // 5 users
     const users = generatedUser.map((item) => {
       return {
         address: item.publicKey,
         share: 1000,
+        endTimestamp: new BN(0),
       };
     });
 
     const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
       padding: [],
       users,
+      metadata: null,
     };
 
     await fullFlow(
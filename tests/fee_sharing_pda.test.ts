@@ -63,12 +63,19 @@ describe("Fee vault pda sharing", () => {
       return {
         address: item.publicKey,
         share: 1000,
+        endTimestamp: new BN(0),
       };
     });
 
     const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
       padding: [],
       users,
+      metadata: null,
     };
 
     const baseKp = Keypair.generate();
@@ -101,8 +108,14 @@ describe("Fee vault pda sharing", () => {
     const users = [];
 
     const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
       padding: [],
       users,
+      metadata: null,
     };
     const baseKp = Keypair.generate();
     const feeVault = deriveFeeVaultPdaAddress(baseKp.publicKey, tokenMint);
@@ -136,12 +149,19 @@ describe("Fee vault pda sharing", () => {
       return {
         address: item.publicKey,
         share: 1000,
+        endTimestamp: new BN(0),
       };
     });
 
     const params: InitializeFeeVaultParameters = {
+      vestingDuration: new BN(0),
+      duplicateUserMode: 0,
+      transferFeeMode: 0,
+      allowPermanentDelegate: 0,
+      padding0: [],
       padding: [],
       users,
+      metadata: null,
     };
 
     await fullFlow(
@@ -0,0 +1,173 @@
+import { LiteSVM } from "litesvm";
+import { PublicKey, Keypair, Transaction } from "@solana/web3.js";
+import {
+  generateUsers,
+  getTokenBalance,
+  sendTransactionOrExpectThrowError,
+  startSvm,
+} from "./common/svm";
+import {
+  createToken,
+  getOrCreateAtA,
+  getProgramErrorCodeHexString,
+} from "./common";
+import {
+  DAMM_V2_PROGRAM_ID,
+  deriveDammV2PoolAuthority,
+} from "./common/damm_v2";
+import {
+  createFeeVaultPda,
+  fundByClaimingFee,
+  getDammV2ClaimPositionFeePayload,
+} from "./common/dfs";
+import { BN } from "bn.js";
+import { expect } from "chai";
+import {
+  AccountLayout,
+  createMintToInstruction,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+
+// mock program deployed at the DAMM v2 address, its claim_position_fee uses any authority the
+// test gives DAMM v2 pool authority over token vault, instead of paying fee
+// built from tests/programs/mock-source-program by the test script, outside of anchor workspace
+const MOCK_SOURCE_PROGRAM_PATH = "./target/deploy/mock_source_program.so";
+
+const FUNDED_AMOUNT = 1_000_000;
+
+describe("Fund by claiming from malicious source program", () => {
+  let svm: LiteSVM;
+  let admin: Keypair;
+  let vaultOwner: Keypair;
+  let shareHolder: Keypair;
+  let tokenMint: PublicKey;
+  let feeVault: PublicKey;
+  let tokenVault: PublicKey;
+  let poolTokenVault: PublicKey;
+
+  beforeEach(async () => {
+    svm = startSvm(MOCK_SOURCE_PROGRAM_PATH);
+    [admin, vaultOwner, shareHolder] = generateUsers(svm, 3);
+    tokenMint = createToken(svm, admin, admin.publicKey, null);
+
+    ({ feeVault, tokenVault } = await createFeeVaultPda(
+      svm,
+      admin,
+      vaultOwner.publicKey,
+      tokenMint,
+      {
+        vestingDuration: new BN(0),
+        duplicateUserMode: 0,
+        transferFeeMode: 0,
+        allowPermanentDelegate: 0,
+        padding0: [],
+        padding: [],
+        users: [
+          {
+            address: shareHolder.publicKey,
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+          {
+            address: PublicKey.unique(),
+            share: 100,
+            endTimestamp: new BN(0),
+          },
+        ],
+        metadata: null,
+      }
+    ));
+
+    const mintTx = new Transaction().add(
+      createMintToInstruction(
+        tokenMint,
+        tokenVault,
+        admin.publicKey,
+        FUNDED_AMOUNT
+      )
+    );
+    mintTx.recentBlockhash = svm.latestBlockhash();
+    mintTx.sign(admin);
+    sendTransactionOrExpectThrowError(svm, mintTx);
+
+    poolTokenVault = getOrCreateAtA(svm, admin, tokenMint, PublicKey.unique());
+  });
+
+  // rewrite token vault as if its owner had granted DAMM v2 pool authority this authority
+  const grantPoolAuthority = (authorityType: "delegate" | "closeAuthority") => {
+    const account = svm.getAccount(tokenVault);
+    const tokenAccount = AccountLayout.decode(account.data);
+    if (authorityType === "delegate") {
+      tokenAccount.delegateOption = 1;
+      tokenAccount.delegate = deriveDammV2PoolAuthority();
+      tokenAccount.delegatedAmount = tokenAccount.amount;
+    } else {
+      tokenAccount.closeAuthorityOption = 1;
+      tokenAccount.closeAuthority = deriveDammV2PoolAuthority();
+    }
+    const data = Buffer.alloc(AccountLayout.span);
+    AccountLayout.encode(tokenAccount, data);
+    svm.setAccount(tokenVault, { ...account, data });
+  };
+
+  const fundByClaimingFromMock = (errorCode: number) => {
+    // accounts of DAMM v2 claim_position_fee, claimed token b is paid to token vault
+    const remainingAccounts = [
+      deriveDammV2PoolAuthority(),
+      PublicKey.unique(),
+      PublicKey.unique(),
+      PublicKey.unique(),
+      tokenVault,
+      PublicKey.unique(),
+      poolTokenVault,
+      PublicKey.unique(),
+      tokenMint,
+      PublicKey.unique(),
+      feeVault,
+      TOKEN_PROGRAM_ID,
+      TOKEN_PROGRAM_ID,
+      PublicKey.unique(),
+      DAMM_V2_PROGRAM_ID,
+    ].map((pubkey, index) => ({
+      pubkey,
+      isSigner: false,
+      isWritable: index === 4 || index === 6,
+    }));
+
+    return fundByClaimingFee(
+      svm,
+      shareHolder,
+      feeVault,
+      tokenVault,
+      remainingAccounts,
+      getDammV2ClaimPositionFeePayload(),
+      DAMM_V2_PROGRAM_ID,
+      errorCode
+    );
+  };
+
+  it("Fail to fund when source program debits token vault", async () => {
+    grantPoolAuthority("delegate");
+
+    await fundByClaimingFromMock(
+      getProgramErrorCodeHexString("TokenVaultBalanceDecreased")
+    );
+
+    expect(getTokenBalance(svm, tokenVault).toNumber()).eq(FUNDED_AMOUNT);
+    expect(getTokenBalance(svm, poolTokenVault).toNumber()).eq(0);
+  });
+
+  it("Fail to fund when source program changes token vault authority", async () => {
+    grantPoolAuthority("closeAuthority");
+
+    await fundByClaimingFromMock(
+      getProgramErrorCodeHexString("TokenVaultAuthorityChanged")
+    );
+
+    const tokenAccount = AccountLayout.decode(svm.getAccount(tokenVault).data);
+    expect(tokenAccount.closeAuthorityOption).eq(1);
+    expect(tokenAccount.closeAuthority.toBase58()).eq(
+      deriveDammV2PoolAuthority().toBase58()
+    );
+  });
+});
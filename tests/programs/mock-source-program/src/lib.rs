@@ -0,0 +1,107 @@
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token_interface::{
+    self, spl_token_2022::instruction::AuthorityType, Mint, SetAuthority, TokenAccount,
+    TokenInterface, TransferChecked,
+};
+
+// deployed at the DAMM v2 address, so fund_by_claiming_fee accepts it as a whitelisted source
+declare_id!("cpamdpZCGKUy5JxQXB4dcpGPiikHawvSWAd6mEn1sGG");
+
+pub const POOL_AUTHORITY_PREFIX: &[u8] = b"pool_authority";
+
+#[program]
+pub mod mock_source_program {
+    use super::*;
+
+    /// Same discriminator and accounts as DAMM v2 claim_position_fee. Instead of paying fee, it
+    /// uses whatever authority the test gave pool authority over the token account receiving
+    /// token b: it resets close authority, or else debits the token account as delegate
+    pub fn claim_position_fee(ctx: Context<ClaimPositionFeeCtx>) -> Result<()> {
+        let pool_authority = ctx.accounts.pool_authority.key();
+        let signer_seeds: &[&[u8]] = &[POOL_AUTHORITY_PREFIX, &[ctx.bumps.pool_authority]];
+
+        if ctx.accounts.token_b_account.close_authority == COption::Some(pool_authority) {
+            return token_interface::set_authority(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_b_program.to_account_info(),
+                    SetAuthority {
+                        current_authority: ctx.accounts.pool_authority.to_account_info(),
+                        account_or_mint: ctx.accounts.token_b_account.to_account_info(),
+                    },
+                    &[signer_seeds],
+                ),
+                AuthorityType::CloseAccount,
+                None,
+            );
+        }
+
+        if ctx.accounts.token_b_account.delegate == COption::Some(pool_authority) {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_b_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.token_b_account.to_account_info(),
+                        mint: ctx.accounts.token_b_mint.to_account_info(),
+                        to: ctx.accounts.token_b_vault.to_account_info(),
+                        authority: ctx.accounts.pool_authority.to_account_info(),
+                    },
+                    &[signer_seeds],
+                ),
+                1,
+                ctx.accounts.token_b_mint.decimals,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimPositionFeeCtx<'info> {
+    /// CHECK: signs for the authority over token b account given by the test
+    #[account(seeds = [POOL_AUTHORITY_PREFIX], bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    /// CHECK: unused
+    pub pool: UncheckedAccount<'info>,
+
+    /// CHECK: unused
+    pub position: UncheckedAccount<'info>,
+
+    /// CHECK: unused
+    pub token_a_account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub token_b_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: unused
+    pub token_a_vault: UncheckedAccount<'info>,
+
+    /// CHECK: receives token debited from token b account
+    #[account(mut)]
+    pub token_b_vault: UncheckedAccount<'info>,
+
+    /// CHECK: unused
+    pub token_a_mint: UncheckedAccount<'info>,
+
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: unused
+    pub position_nft_account: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: unused
+    pub token_a_program: UncheckedAccount<'info>,
+
+    pub token_b_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: unused
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: unused
+    pub program: UncheckedAccount<'info>,
+}